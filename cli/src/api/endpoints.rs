@@ -0,0 +1,217 @@
+//! Requests and responses of the gateway's REST routes. Adding a route means
+//! adding its request struct and an `endpoint!` line.
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+
+use super::Endpoint;
+
+macro_rules! endpoint {
+    ($request:ty, $method:ident, $path:literal, $response:ty) => {
+        impl Endpoint for $request {
+            type Response = $response;
+            const METHOD: Method = Method::$method;
+            const PATH: &'static str = $path;
+        }
+    };
+}
+
+#[derive(Serialize)]
+pub struct Login<'a> {
+    pub email: &'a str,
+    pub password: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Signup<'a> {
+    pub nickname: &'a str,
+    pub email: &'a str,
+    pub password: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct RemoteLogin<'a> {
+    pub cli_port: u16,
+    pub cli_state: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct SetNickname<'a> {
+    pub nickname: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Login2fa<'a> {
+    pub totp_code: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Verify {}
+
+#[derive(Serialize)]
+pub struct Refresh<'a> {
+    pub refresh_token: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Logout {}
+
+#[derive(Serialize)]
+pub struct Account {}
+
+#[derive(Serialize)]
+pub struct EnableTotp<'a> {
+    pub totp_code: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct DisableTotp<'a> {
+    pub totp_code: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Profile {}
+
+#[derive(Serialize)]
+pub struct UpdateProfile<'a> {
+    pub nickname: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct UpdateAccount<'a> {
+    pub email: &'a str,
+    pub current_password: &'a str,
+    pub new_password: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct DeleteAccount<'a> {
+    pub password: Option<&'a str>,
+    pub totp_code: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct MyStatistics {}
+
+#[derive(Serialize)]
+pub struct MyMatches {
+    pub limit: u32,
+}
+
+#[derive(Serialize)]
+pub struct Health {}
+
+endpoint!(Login<'_>, POST, "/api/login", LoginResponse);
+endpoint!(Signup<'_>, POST, "/api/signup", LoginResponse);
+endpoint!(RemoteLogin<'_>, GET, "/api/auth/42", RedirectResponse);
+endpoint!(SetNickname<'_>, POST, "/api/profile/set-nickname", NicknameResponse);
+endpoint!(Login2fa<'_>, POST, "/api/auth/2fa/login", LoginResponse);
+endpoint!(Verify, GET, "/api/verify", IgnoredAny);
+endpoint!(Refresh<'_>, POST, "/api/auth/refresh", RefreshResponse);
+endpoint!(Logout, POST, "/api/logout", IgnoredAny);
+endpoint!(Account, GET, "/api/account", AccountResponse);
+endpoint!(EnableTotp<'_>, POST, "/api/auth/2fa/enable", IgnoredAny);
+endpoint!(DisableTotp<'_>, POST, "/api/auth/2fa/disable", IgnoredAny);
+endpoint!(Profile, GET, "/api/profile", ProfileResponse);
+endpoint!(UpdateProfile<'_>, PUT, "/api/profile", UpdateProfileResponse);
+endpoint!(UpdateAccount<'_>, POST, "/api/account/update", IgnoredAny);
+endpoint!(DeleteAccount<'_>, POST, "/api/account/delete", IgnoredAny);
+endpoint!(MyStatistics, GET, "/api/my-statistics", StatisticsResponse);
+endpoint!(MyMatches, GET, "/api/my-matches", MatchesResponse);
+endpoint!(Health, GET, "/api/health", IgnoredAny);
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum BoolOrString {
+    Bool(bool),
+    String(String),
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct LoginResponse {
+    pub success: bool,
+    pub token: String,
+    pub action_required: BoolOrString,
+    pub user: Option<User>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct User {
+    pub id: usize,
+    pub nickname: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RedirectResponse {
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NicknameResponse {
+    pub success: bool,
+    pub token: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshResponse {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountResponse {
+    success: bool,
+    pub auth_method: String,
+    pub email: Option<String>,
+    pub totp_enabled: bool,
+    pub enable_totp_uri: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProfileResponse {
+    pub nickname: Option<String>,
+    pub email: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct UpdateProfileResponse {
+    success: bool,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct StatisticsResponse {
+    pub games_played: u64,
+    pub games_won: u64,
+    pub games_lost: u64,
+    pub win_rate: u64,
+    pub avg_score: u64,
+    pub total_score: u64,
+    pub last_game_date: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MatchesResponse {
+    pub matches: Vec<Match>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Match {
+    pub id: u64,
+    pub game_type: String,
+    pub room_id: Option<String>,
+    pub played_at: String,
+    pub opponent_nickname: Option<String>,
+    pub my_score: i64,
+    pub opponent_score: Option<i64>,
+    pub result: String,
+}
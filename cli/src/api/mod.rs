@@ -0,0 +1,132 @@
+pub mod endpoints;
+
+use std::{error::Error, fmt::Display};
+
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::server::Server;
+
+// The web application firewall in front of the gateway flags clients that
+// don't look like a browser
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/134.0.0.0 Safari/537.3";
+
+/// A REST endpoint of the gateway. The implementing type is the request,
+/// sent as query string for GET and as JSON body otherwise.
+pub trait Endpoint: Serialize {
+    type Response: DeserializeOwned;
+    const METHOD: Method;
+    const PATH: &'static str;
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    Client(String),
+    ConnectionError,
+    Status(StatusCode, Option<String>),
+    InvalidResponse,
+}
+
+impl ApiError {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status(status, _) => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The server refused the token, as opposed to a missing route or a rate limit.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self.status(), Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN))
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Client(err) => write!(f, "Unable to set up the HTTP client: {}", err),
+            Self::ConnectionError => write!(f, "Connection Error"),
+            Self::Status(_, Some(message)) => write!(f, "{}", message),
+            Self::Status(status, None) if status.is_server_error() => write!(f, "Internal Server Error"),
+            Self::Status(status, None) => write!(f, "Request failed: {}", status),
+            Self::InvalidResponse => write!(f, "Invalid response received from server"),
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+/// Error body of the services, most send `error` and some add `message`.
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: Option<String>,
+    message: Option<String>,
+}
+
+/// Client for the REST API of one host. Clones share the connection pool, so
+/// hand them to tasks freely.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new() -> Result<Self, ApiError> {
+        let http = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|err| ApiError::Client(err.to_string()))?;
+
+        Ok(Self {
+            http,
+            base_url: String::new(),
+            token: None,
+        })
+    }
+
+    pub fn for_host(&self, host: &Server) -> Self {
+        Self {
+            http: self.http.clone(),
+            base_url: host.rest(""),
+            token: None,
+        }
+    }
+
+    /// Sends `token` as bearer token with every request.
+    pub fn with_token(&self, token: &str) -> Self {
+        Self {
+            token: Some(token.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub async fn call<E: Endpoint>(&self, request: &E) -> Result<E::Response, ApiError> {
+        let url = format!("{}{}", self.base_url, E::PATH);
+        let mut builder = self.http.request(E::METHOD, url);
+
+        builder = match E::METHOD == Method::GET {
+            true => builder.query(request),
+            false => builder.json(request),
+        };
+        if let Some(token) = &self.token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let response = builder.send().await.map_err(|_| ApiError::ConnectionError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<ErrorBody>()
+                .await
+                .ok()
+                .and_then(|body| body.error.or(body.message));
+            return Err(ApiError::Status(status, message));
+        }
+
+        response.json().await.map_err(|_| ApiError::InvalidResponse)
+    }
+}
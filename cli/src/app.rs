@@ -1,44 +1,100 @@
-use std::{collections::HashMap, error::Error, fmt::Display, net::SocketAddr, time::Duration};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    error::Error,
+    fmt::Display,
+    future::Future,
+    io::Write,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
 
+use base64::{Engine, engine::general_purpose::STANDARD};
 use futures_util::{FutureExt, StreamExt, future};
-use http::{Response, StatusCode};
+use http::{HeaderValue, Method, Response, StatusCode, header};
 use http_body_util::Full;
-use hyper::{Request, body::Bytes, server::conn::http1, service::service_fn};
+use hyper::{
+    Request,
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
 use hyper_util::rt::TokioIo;
 use ratatui::DefaultTerminal;
 use tokio::{
     net::TcpListener,
     select,
-    sync::mpsc::{self, Sender},
-    time,
+    sync::{
+        mpsc::{self, Sender},
+        oneshot,
+    },
+    time::{self, Instant},
 };
 
 use crate::{
-    auth::{self, BoolOrString, LoginErrors, TotpErrors},
+    api::{
+        ApiClient, ApiError,
+        endpoints::{AccountResponse, BoolOrString, ProfileResponse},
+    },
+    auth::{self, LoginErrors, TotpErrors},
+    capabilities::Capabilities,
+    config::{Config, ConfigErrors},
+    game::keymap::Keymap,
+    hosts::{self, Health, HostStore},
+    server::{Server, ServerErrors},
+    session::{Session, SessionErrors, SessionStore},
     ui::{
-        game::Game,
+        account::{AccountPage, AccountRequest},
+        account_switcher::{AccountChoice, AccountSwitcherPage},
+        game::{Game, GameSettings},
+        graphics,
         game_lobby::GameLobbyPage,
         game_over::GameOverPage,
         gamemode::{GameModePage, GameModes},
-        host_selector::HostSelectorPage,
+        host_selector::{HostRequest, HostSelectorPage},
         join_room::JoinRoomPage,
         login::LoginPage,
+        security::SecurityPage,
+        settings::SettingsPage,
+        signup::SignupPage,
         nickname_page::NicknamePage,
         pages::{LoginType, PageResults},
+        remote_login::RemoteLoginPage,
+        theme::Theme,
         totp::TotpPage,
     },
     websocket::{
         SocketIoClient,
         events::{errors::EventError, request::CreateRoomRequest, websocketevents::SocketEvents},
+        livechat::{InvitationStatus, LiveChatClient, LiveChatEvents},
     },
 };
 
 use super::ui::pages::Pages;
 
+// The server keeps a finished room around for 5 seconds and refuses new rooms until then
+const REMATCH_ROOM_ATTEMPTS: usize = 5;
+const REMATCH_RETRY_DELAY: Duration = Duration::from_secs(2);
+/// The game server starts a match five seconds after `game_start`.
+const DEFAULT_START_DELAY: Duration = Duration::from_secs(5);
+/// Exiting waits at most this long for the server to end the session.
+const LOGOUT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long before the access token expires it gets refreshed.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum FatalErrors {
     RenderingError,
     KeyboardEnhancementFlagsError(std::io::Error),
+    MouseCaptureError(std::io::Error),
+    ConfigError(ConfigErrors),
+    ApiClientError(ApiError),
+    InvalidHost(ServerErrors),
 }
 
 impl Error for FatalErrors {}
@@ -54,32 +110,86 @@ impl Display for FatalErrors {
                     err
                 )
             }
+            Self::MouseCaptureError(err) => write!(f, "Unable to toggle mouse capture: {}", err),
+            Self::ConfigError(err) => write!(f, "{}", err),
+            Self::ApiClientError(err) => write!(f, "{}", err),
+            Self::InvalidHost(err) => write!(f, "{}", err),
         }
     }
 }
 
+/// Where to go once logged in instead of the mode selector.
+#[derive(Debug)]
+pub enum Start {
+    Play { single: bool },
+    Join(String),
+    /// A room created before the TUI started
+    Lobby {
+        host: Server,
+        session: Session,
+        socket: Box<SocketIoClient>,
+        room_id: String,
+    },
+    /// A login of the command line, `start` follows once it is taken over
+    LoggedIn {
+        host: Server,
+        session: Session,
+        start: Box<Start>,
+    },
+}
+
 #[derive(Debug)]
 enum WsOrWeb {
-    Websocket(SocketIoClient),
-    Webserver(TcpListener),
+    Websocket(Box<SocketIoClient>),
+    Webserver(CallbackListener),
 }
 
 #[derive(Debug)]
 pub struct App {
-    host: Option<String>,
+    api: ApiClient,
+    host: Option<Server>,
     auth_token: Option<String>,
+    refresh_token: Option<String>,
+    refresh_at: Option<Instant>,
     current_page: Pages,
     socket: Option<WsOrWeb>,
-    kitty_protocol_support: bool,
+    capabilities: Capabilities,
+    image_on_screen: bool,
+    config: Config,
+    theme: Theme,
+    theme_name: String,
+    keymap: Keymap,
+    singleplayer: bool,
+    rematch: Option<oneshot::Sender<()>>,
+    rematch_room: Option<String>,
+    start_delay: Duration,
+    sessions: SessionStore,
+    hosts: HostStore,
+    start: Option<Start>,
 }
 
 #[derive(Debug)]
 enum ChannelEvents {
-    LoginSuccess((String, String)),
+    SessionValid((Server, String, Option<String>)),
+    HostHealth((String, Health)),
+    SessionInvalid((Server, Session, Option<LoginType>)),
+    SessionError(LoginErrors),
+    LoginSuccess((Server, String, Option<String>)),
     LoginError(LoginErrors),
+    SignupError(LoginErrors),
+    AccountLoaded(AccountResponse),
+    AccountError(LoginErrors),
+    ProfileLoaded(ProfileResponse),
+    AccountUpdated((String, Option<String>)),
+    AccountDeleted,
+    AccountRequestError(LoginErrors),
+    TotpChanged(bool),
+    TotpChangeError(TotpErrors),
     NicknameError(LoginErrors),
-    TotpRequired((String, String)),
-    TotpSuccess(String),
+    TotpRequired((Server, String)),
+    TotpSuccess((String, Option<String>)),
+    /// The spent refresh token and the session that replaces it.
+    TokenRefreshed((String, Session)),
     TotpError(TotpErrors),
     RemoteRedirect(String),
     RemoteRedirectCallback((String, bool)),
@@ -87,95 +197,190 @@ enum ChannelEvents {
     RoomCreated((SocketIoClient, String)),
     RoomJoined(SocketIoClient),
     RoomJoinError(EventError),
+    RematchRoomCreated((Box<SocketIoClient>, String)),
+    RematchRoomError((Box<SocketIoClient>, EventError)),
+    RematchWaiting(String),
+    RematchFailed(String),
+    RematchJoin(String),
+}
+
+/// Connections to the callback listener get this long to send their request.
+const CALLBACK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CALLBACK_ACCEPT_RETRY: Duration = Duration::from_millis(100);
+const CALLBACK_PAGE: &str = "<!DOCTYPE html>
+<html>
+<head><meta charset=\"utf-8\"><title>ft_transcendence</title></head>
+<body style=\"font-family: sans-serif; text-align: center; margin-top: 4em\">
+<h1>Login complete</h1>
+<p>You can close this tab and switch back to the terminal.</p>
+</body>
+</html>
+";
+
+/// Local listener the 42 login calls back to.
+#[derive(Debug)]
+struct CallbackListener {
+    listener: TcpListener,
+    state: Arc<CallbackState>,
 }
 
+#[derive(Debug)]
+struct CallbackState {
+    /// Random value sent along the login request, the callback has to return it.
+    expected: String,
+    /// Set by the first valid callback, later ones are refused.
+    done: AtomicBool,
+    /// Origin of the server the login runs on, the only page that may read
+    /// the answers.
+    origin: String,
+}
+
+/// Serves every connection to `server` in its own task. Only the first
+/// callback with the expected state is reported, preflight, favicon and other
+/// requests are answered without touching the login.
 async fn wait_for_webserver_events(
-    server: &mut TcpListener,
+    server: &CallbackListener,
     tx: &Sender<ChannelEvents>,
 ) -> Result<SocketEvents, EventError> {
-    let (stream, _) = match server.accept().await {
-        Ok(conn) => conn,
-        Err(_) => return Err(EventError::ConnectionError),
-    };
+    loop {
+        let stream = match server.listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(_) => {
+                time::sleep(CALLBACK_ACCEPT_RETRY).await;
+                continue;
+            }
+        };
 
-    let service = service_fn(move |req: Request<hyper::body::Incoming>| {
+        let state = server.state.clone();
         let tx = tx.clone();
-        async move {
-            let query = req.uri().query().unwrap_or("");
-            let params: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
-                .into_owned()
-                .collect();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle_callback(req, state.clone(), tx.clone()));
+            let connection = http1::Builder::new()
+                .keep_alive(false)
+                .serve_connection(TokioIo::new(stream), service);
+            time::timeout(CALLBACK_REQUEST_TIMEOUT, connection).await.ok();
+        });
+    }
+}
 
-            let token = params.get("token");
-            let nickname_required = params.get("nickname_required");
+async fn handle_callback(
+    req: Request<Incoming>,
+    state: Arc<CallbackState>,
+    tx: Sender<ChannelEvents>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let query = req.uri().query().unwrap_or("");
+    let params: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+
+    let mut response = match (req.method(), req.uri().path()) {
+        (&Method::OPTIONS, "/") => {
+            // Browsers ask before calling a local address from a public page
+            let mut response = callback_response(StatusCode::NO_CONTENT, "");
+            let headers = response.headers_mut();
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET"));
+            headers.insert("Access-Control-Allow-Private-Network", HeaderValue::from_static("true"));
+            response
+        }
+        (&Method::GET, "/") => {
+            // Anything on this machine can call the listener, only the login
+            // this client started knows the state
+            if params.get("state") != Some(&state.expected) {
+                return Ok(callback_response(StatusCode::FORBIDDEN, "Unknown login"));
+            }
 
-            match (token, nickname_required) {
+            let nickname_required = match params.get("nickname_required").map(String::as_str) {
+                Some("true") => Some(true),
+                Some("false") => Some(false),
+                _ => None,
+            };
+            match (params.get("token"), nickname_required) {
                 (Some(token), Some(nickname_required)) => {
-                    let nickname_required = match nickname_required.as_str() {
-                        "true" => true,
-                        "false" => false,
-                        _ => {
-                            let response = Response::builder()
-                                .status(StatusCode::BAD_REQUEST)
-                                .header("Access-Control-Allow-Origin", "*")
-                                .body(Full::new(Bytes::from("Bad Request")))
-                                .unwrap();
-                            tx.send(ChannelEvents::RemoteRedirectError(
-                                LoginErrors::InvalidResponse,
-                            ))
-                            .await
-                            .unwrap();
-                            return Ok::<_, hyper::Error>(response);
+                    if state.done.swap(true, Ordering::SeqCst) {
+                        return Ok(callback_response(StatusCode::GONE, "The login is already complete"));
+                    }
+                    tx.send(ChannelEvents::RemoteRedirectCallback((token.clone(), nickname_required)))
+                        .await
+                        .ok();
+                    match req.headers().contains_key(header::ORIGIN) {
+                        // The frontend fetches the callback and shows its own message
+                        true => callback_response(StatusCode::OK, "Login complete"),
+                        // Opened in the browser itself, e.g. the URL the page shows
+                        false => {
+                            let mut response = callback_response(StatusCode::OK, CALLBACK_PAGE);
+                            response
+                                .headers_mut()
+                                .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+                            response
                         }
-                    };
-                    tx.send(ChannelEvents::RemoteRedirectCallback((
-                        token.clone(),
-                        nickname_required,
-                    )))
-                    .await
-                    .unwrap();
-                    let response = Response::builder()
-                        .header("Access-Control-Allow-Origin", "*")
-                        .body(Full::new(Bytes::from("LGTM")))
-                        .unwrap();
-                    Ok::<_, hyper::Error>(response)
+                    }
                 }
                 (_, _) => {
-                    let response = Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .header("Access-Control-Allow-Origin", "*")
-                        .body(Full::new(Bytes::from("Bad Request")))
-                        .unwrap();
-                    tx.send(ChannelEvents::RemoteRedirectError(
-                        LoginErrors::InvalidResponse,
-                    ))
+                    tx.send(ChannelEvents::RemoteRedirectError(LoginErrors::Api(
+                        ApiError::InvalidResponse,
+                    )))
                     .await
-                    .unwrap();
-                    Ok::<_, hyper::Error>(response)
+                    .ok();
+                    callback_response(StatusCode::BAD_REQUEST, "Bad Request")
                 }
             }
         }
-    });
-
-    let io = TokioIo::new(stream);
+        (_, _) => callback_response(StatusCode::NOT_FOUND, "Not Found"),
+    };
 
-    http1::Builder::new()
-        .serve_connection(io, service)
-        .await
-        .ok();
+    // The page of the server calls the listener, it may read the answer
+    response.headers_mut().insert(header::VARY, HeaderValue::from_static("Origin"));
+    if let Some(origin) = req.headers().get(header::ORIGIN)
+        && origin.as_bytes() == state.origin.as_bytes()
+    {
+        response
+            .headers_mut()
+            .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+    }
+    Ok(response)
+}
 
-    Err(EventError::ConnectionError)
+fn callback_response(status: StatusCode, body: &'static str) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from_static(body.as_bytes())));
+    *response.status_mut() = status;
+    response
 }
 
 impl App {
-    pub fn new(kitty_protocol_support: bool) -> Self {
-        Self {
-            host: None,
+    pub fn new(
+        capabilities: Capabilities,
+        config: Config,
+        theme: Theme,
+        host: Option<Server>,
+        start: Option<Start>,
+    ) -> Result<Self, FatalErrors> {
+        let hosts = HostStore::load().unwrap_or_default();
+        Ok(Self {
+            api: ApiClient::new().map_err(FatalErrors::ApiClientError)?,
+            host,
             auth_token: None,
-            current_page: Pages::HostSelector(HostSelectorPage::new()),
+            refresh_token: None,
+            refresh_at: None,
+            current_page: Pages::HostSelector(HostSelectorPage::new(hosts.list())),
+            hosts,
             socket: None,
-            kitty_protocol_support,
-        }
+            capabilities,
+            image_on_screen: false,
+            theme_name: config.theme_name().to_string(),
+            start_delay: config
+                .start_delay_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_START_DELAY),
+            sessions: match config.remember_session() {
+                true => SessionStore::load().unwrap_or_default(),
+                false => SessionStore::default(),
+            },
+            keymap: config.keymap().map_err(FatalErrors::ConfigError)?,
+            config,
+            theme,
+            singleplayer: false,
+            rematch: None,
+            rematch_room: None,
+            start,
+        })
     }
 
     async fn wait_for_socket_events(
@@ -191,22 +396,62 @@ impl App {
 
     pub async fn run(mut self, terminal: &mut DefaultTerminal) -> Result<(), FatalErrors> {
         let mut reader = crossterm::event::EventStream::new();
-        let mut interval = time::interval(Duration::from_micros(1_000_000 / 60));
+        let mut interval = time::interval(self.config.tick());
         let (tx, mut rx) = mpsc::channel(8);
 
+        match self.start.take() {
+            Some(Start::Lobby { host, session, socket, room_id }) => {
+                self.set_host(host);
+                self.set_tokens(session.token, session.refresh_token);
+                self.socket = Some(WsOrWeb::Websocket(socket));
+                self.current_page = Pages::GameLobby(GameLobbyPage::new(room_id));
+            }
+            Some(Start::LoggedIn { host, session, start }) => {
+                self.set_host(host);
+                self.set_tokens(session.token, session.refresh_token);
+                self.start = Some(*start);
+                self.logged_in(&tx);
+            }
+            start => {
+                self.start = start;
+                self.restore_session(&tx);
+            }
+        }
+
         loop {
+            let refresh_at = self.refresh_at;
             select! {
                 event = self.wait_for_socket_events(&tx) => {
                     match (event, &mut self.current_page) {
                         (Ok(SocketEvents::GameStart(gamestartevent)), _) => {
-                            self.current_page = Pages::Game(Game::new(gamestartevent, &terminal.get_frame()));
+                            self.rematch = None;
+                            self.rematch_room = None;
+                            self.current_page = Pages::Game(Box::new(Game::new(
+                                gamestartevent,
+                                &terminal.get_frame(),
+                                &self.capabilities,
+                                GameSettings {
+                                    hud: self.config.hud,
+                                    keymap: self.keymap,
+                                    theme: self.theme_name.clone(),
+                                    themes: self.config.theme_names(),
+                                    start_delay: self.start_delay,
+                                    start_notification: self.config.start_notification,
+                                    movement_timeout: self.config.movement_timeout(),
+                                    account: self.account_nickname(),
+                                },
+                            )));
                         },
                         (Ok(SocketEvents::GameState(gamestateevent)), Pages::Game(game)) => {
-                            game.update(&gamestateevent);
+                            if let Some(measured) = game.update(&gamestateevent)
+                                && self.config.start_delay_ms.is_none()
+                            {
+                                self.start_delay = (self.start_delay + measured) / 2;
+                            }
                         },
                         (Ok(SocketEvents::GameOver(gameoverevent)), Pages::Game(game)) => {
                             let result = game.game_over(&gameoverevent);
-                            self.current_page = Pages::GameOver(GameOverPage::new(result));
+                            self.current_page = Pages::GameOver(Box::new(GameOverPage::new(result)));
                         },
                         (Ok(SocketEvents::GameAborted(_)), _) => self.abort_game().await,
                         (Ok(SocketEvents::GamePauseState(is_paused)), Pages::Game(game)) => {
@@ -221,49 +466,67 @@ impl App {
 
                 Some(Ok(event)) = reader.next().fuse() => {
                     match event {
-                      crossterm::event::Event::Key(key) => {
-                          match self.current_page.key_event(&event, key.kind) {
+                      crossterm::event::Event::Key(_) | crossterm::event::Event::Mouse(_) => {
+                          let result = match &event {
+                              crossterm::event::Event::Key(key) => self.current_page.key_event(&event, key.kind),
+                              crossterm::event::Event::Mouse(mouse) => self.current_page.mouse_event(mouse),
+                              _ => None,
+                          };
+                          match result {
                               Some(PageResults::HostSelected((host, login_type))) => {
-                                self.host = Some(host.clone());
-                                match login_type {
-                                  LoginType::LocalLogin => {
-                                    self.current_page = Pages::Login(LoginPage::new());
-                                  },
-                                  LoginType::RemoteLogin => {
-                                    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
-                                    let webserver = TcpListener::bind(addr).await;
-                                    match webserver {
-                                      Ok(webserver) => {
-                                        let addr = webserver.local_addr();
-                                        match addr {
-                                          Ok(addr) => {
-                                            let port = addr.port();
-                                            let tx = tx.clone();
-                                            self.socket = Some(WsOrWeb::Webserver(webserver));
-                                            tokio::spawn(async move {
-                                              match auth::remotelogin(&host, port).await {
-                                                Ok(response) => tx.send(ChannelEvents::RemoteRedirect(response.url)).await.unwrap(),
-                                                Err(err) => tx.send(ChannelEvents::RemoteRedirectError(err)).await.unwrap(),
-                                              }
-                                            });
-                                          },
-                                          Err(_) => tx.send(ChannelEvents::LoginError(LoginErrors::Unknown("Unable to fetch Port from local webserver".to_string()))).await.unwrap(),
-                                        }
-                                      }
-                                      Err(_) => tx.send(ChannelEvents::LoginError(LoginErrors::Unknown("Unable to bind webserver".to_string()))).await.unwrap(),
+                                self.hosts.used(host.as_str(), login_type);
+                                self.hosts.save().ok();
+                                self.set_host(host.clone());
+                                let mut accounts = self.sessions.accounts(host.as_str());
+                                match (login_type, accounts.len()) {
+                                  (LoginType::Signup, _) | (_, 0) => self.start_login(host, login_type, &tx).await,
+                                  (_, 1) => {
+                                    if let Pages::HostSelector(page) = &mut self.current_page {
+                                      page.host_info(Some(format!("Restoring session for {}", host)));
                                     }
+                                    let session = accounts.remove(0);
+                                    tokio::spawn(verify_session(self.api.for_host(&host), host, session, Some(login_type), tx.clone()));
                                   },
+                                  (_, _) => self.show_accounts(&host),
+                                }
+                              },
+                              Some(PageResults::Accounts(choice)) => self.account_choice(choice, &tx).await,
+                              Some(PageResults::Host(request)) => {
+                                match request {
+                                    HostRequest::ToggleBookmark(url) => self.hosts.toggle_bookmark(&url),
+                                    HostRequest::Rename(url, name) => self.hosts.rename(&url, name),
+                                    HostRequest::Remove(url) => self.hosts.remove(&url),
+                                }
+                                let saved = self.hosts.save();
+                                if let Pages::HostSelector(page) = &mut self.current_page {
+                                    page.set_hosts(self.hosts.list());
+                                    if let Err(err) = saved {
+                                        page.host_error(&LoginErrors::Unknown(err.to_string()));
+                                    }
+                                }
+                              },
+                              Some(PageResults::RemoteCallback((token, nickname_required))) => {
+                                self.remote_logged_in(token, nickname_required, &tx);
+                              },
+                              Some(PageResults::CancelRemoteLogin) => self.cancel_remote_login(None),
+                              Some(PageResults::CopyToClipboard(text)) => {
+                                let backend = terminal.backend_mut();
+                                let copied = backend
+                                    .write_all(clipboard_sequence(&text).as_bytes())
+                                    .and_then(|()| backend.flush());
+                                if let (Ok(()), Pages::RemoteLogin(page)) = (copied, &mut self.current_page) {
+                                    page.info("Copied, if the terminal allows access to the clipboard".to_string());
                                 }
                               },
                               Some(PageResults::NicknameSelected(nickname)) => {
-                                let token = self.auth_token.clone().unwrap();
+                                let api = self.api();
                                 let host = self.host.clone().unwrap();
                                 let tx = tx.clone();
                                 tokio::spawn(async move {
-                                  match auth::set_nickname(&host, &token, &nickname).await {
+                                  match auth::set_nickname(&api, &nickname).await {
                                     Ok(response) => {
                                       match (response.success, response.token, response.error) {
-                                        (true, Some(token), _) => tx.send(ChannelEvents::LoginSuccess((host, token))).await.unwrap(),
+                                        (true, Some(token), _) => tx.send(ChannelEvents::LoginSuccess((host, token, response.refresh_token))).await.unwrap(),
                                         (false, _, Some(error)) => tx.send(ChannelEvents::NicknameError(LoginErrors::Unknown(error))).await.unwrap(),
                                         (_, _, _) => tx.send(ChannelEvents::NicknameError(LoginErrors::Unknown("someone changed backend code ig".to_string()))).await.unwrap(),
                                       }
@@ -273,15 +536,14 @@ impl App {
                                 });
                               },
                               Some(PageResults::Login((email, password))) => {
+                                let api = self.api();
                                 let host = self.host.clone().unwrap();
-                                let email = email.clone();
-                                let password = password.clone();
                                 let tx = tx.clone();
                                 tokio::spawn(async move {
-                                    match auth::login(&host, &email, &password).await {
+                                    match auth::login(&api, &email, &password).await {
                                         Ok(response) => {
                                           if let BoolOrString::Bool(false) = response.action_required {
-                                            tx.send(ChannelEvents::LoginSuccess((host, response.token))).await.unwrap();
+                                            tx.send(ChannelEvents::LoginSuccess((host, response.token, response.refresh_token))).await.unwrap();
                                           } else {
                                             tx.send(ChannelEvents::TotpRequired((host, response.token))).await.unwrap();
                                           }
@@ -290,82 +552,138 @@ impl App {
                                     }
                                 });
                               },
-                              Some(PageResults::Totp(totp_code)) => {
-                                if let (Some(host), Some(auth_token)) = (&self.host, &self.auth_token) {
-                                  let host = host.clone();
-                                  let auth_token = auth_token.clone();
-                                  let totp_code = totp_code.clone();
-                                  let tx = tx.clone();
-                                  tokio::spawn(async move {
-                                    match auth::login2fa(&host, &auth_token, &totp_code).await {
-                                      Ok(response) => tx.send(ChannelEvents::TotpSuccess(response.token)).await.unwrap(),
-                                      Err(totperror) => tx.send(ChannelEvents::TotpError(totperror)).await.unwrap(),
-                                    }
-                                  });
-                                }
+                              Some(PageResults::Signup((nickname, email, password))) => {
+                                let api = self.api();
+                                let host = self.host.clone().unwrap();
+                                let tx = tx.clone();
+                                tokio::spawn(async move {
+                                    let event = match auth::signup(&api, &nickname, &email, &password).await {
+                                        Ok(response) => ChannelEvents::LoginSuccess((host, response.token, response.refresh_token)),
+                                        Err(error) => ChannelEvents::SignupError(error),
+                                    };
+                                    tx.send(event).await.unwrap();
+                                });
                               },
-                              Some(PageResults::GameModeChosen(mode)) => {
-                                match (mode, self.host.as_ref(), self.auth_token.as_ref()) {
-                                    (GameModes::SinglePlayer, Some(host), Some(token)) => {
-                                        let host = host.clone();
-                                        let token = token.clone();
-                                        let tx = tx.clone();
-                                        tokio::spawn(async move {
-                                            match create_singleplayer_game(&get_endpoint(&host, &token), &token).await {
-                                                Ok((client, _)) => tx.send(ChannelEvents::RoomJoined(client)).await.unwrap(),
-                                                Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
-                                            }
-                                        });
-                                    }
-                                    (GameModes::CreateRoom, Some(host), Some(token)) => {
-                                        let host = host.clone();
-                                        let token = token.clone();
-                                        let tx = tx.clone();
-                                        tokio::spawn(async move {
-                                            match create_join_room(&get_endpoint(&host, &token), &token, None).await {
-                                                Ok((client, Some(room_id))) => tx.send(ChannelEvents::RoomCreated((client, room_id))).await.unwrap(),
-                                                Ok((_, None)) => panic!("create_join_room returned no room_id after creating a room"),
-                                                Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
-                                            }
-                                        });
+                              Some(PageResults::SetTotp((enable, totp_code))) if self.auth_token.is_some() => {
+                                let api = self.api();
+                                let tx = tx.clone();
+                                tokio::spawn(async move {
+                                  match auth::set_totp(&api, enable, &totp_code).await {
+                                    Ok(()) => {
+                                      tx.send(ChannelEvents::TotpChanged(enable)).await.unwrap();
+                                      load_account(api, tx).await;
                                     },
-                                    (GameModes::JoinRoom, _, _) => self.current_page = Pages::JoinRoom(JoinRoomPage::new()),
-                                    (_, _, _) => (),
-                                }
+                                    Err(error) => tx.send(ChannelEvents::TotpChangeError(error)).await.unwrap(),
+                                  }
+                                });
                               },
-                              Some(PageResults::BackToMenu) | Some(PageResults::GameOver) => {
-                                  self.abort_game().await;
+                              Some(PageResults::Account(request)) if self.auth_token.is_some() => {
+                                tokio::spawn(account_request(self.api(), request, tx.clone()));
                               },
-                              Some(PageResults::JoinRoom(room_id)) => {
-                                let room_id = room_id.clone();
-                                let host = self.host.clone();
-                                let auth_token = self.auth_token.clone();
+                              Some(PageResults::Totp(totp_code)) if self.auth_token.is_some() => {
+                                let api = self.api();
                                 let tx = tx.clone();
                                 tokio::spawn(async move {
-                                    if let (Some(host), Some(token)) = (host.as_ref(), auth_token.as_ref()) {
-                                        match create_join_room(&get_endpoint(host, &token), token, Some(room_id)).await {
-                                            Ok((client, _)) => tx.send(ChannelEvents::RoomJoined(client)).await.unwrap(),
-                                            Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
-                                        }
-                                    }
+                                  match auth::login2fa(&api, &totp_code).await {
+                                    Ok(response) => tx.send(ChannelEvents::TotpSuccess((response.token, response.refresh_token))).await.unwrap(),
+                                    Err(totperror) => tx.send(ChannelEvents::TotpError(totperror)).await.unwrap(),
+                                  }
                                 });
                               },
+                              Some(PageResults::GameModeChosen(mode)) => self.choose_mode(mode, &tx),
+                              Some(PageResults::BackToMenu) | Some(PageResults::GameOver) => {
+                                  self.abort_game().await;
+                              },
+                              Some(PageResults::JoinRoom(room_id)) => self.join_room(room_id, &tx),
                               Some(PageResults::UpdatePaddleMovement(paddle_directions)) => {
-                                  if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut() {
-                                    if socket.paddle_move(paddle_directions).await.is_err() {
-                                        self.abort_game().await;
-                                    }
+                                  if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut()
+                                    && socket.paddle_move(paddle_directions).await.is_err() {
+                                      self.abort_game().await;
                                   }
                               },
                               Some(PageResults::GamePaused(is_paused)) => {
-                                  if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut() {
-                                    if socket.pause_game(is_paused).await.is_err() {
-                                        self.abort_game().await;
-                                    }
+                                  if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut()
+                                    && socket.pause_game(is_paused).await.is_err() {
+                                      self.abort_game().await;
                                   }
                               },
-                              Some(PageResults::Exit) => break Ok(()),
-                              None => (),
+                              Some(PageResults::Rematch) => {
+                                if let Pages::GameOver(page) = &mut self.current_page
+                                    && let Some(WsOrWeb::Websocket(mut socket)) = self.socket.take()
+                                {
+                                    let tx = tx.clone();
+                                    if !self.singleplayer && !page.is_owner() {
+                                        let (cancel, cancelled) = oneshot::channel();
+                                        let host = self.host.clone().unwrap();
+                                        let token = self.auth_token.clone().unwrap();
+                                        page.rematch_waiting(format!("Waiting for {} to start the rematch", page.opponent().nickname));
+                                        tokio::spawn(wait_for_rematch(host.socket("livechat/", &token), token, tx, cancelled));
+                                        self.socket = Some(WsOrWeb::Websocket(socket));
+                                        self.rematch = Some(cancel);
+                                    } else {
+                                        let singleplayer = self.singleplayer;
+                                        page.rematch_waiting("Creating a new room".to_string());
+                                        tokio::spawn(async move {
+                                            match create_rematch_room(&mut socket, singleplayer).await {
+                                                Ok(room_id) => tx.send(ChannelEvents::RematchRoomCreated((socket, room_id))).await.unwrap(),
+                                                Err(error) => tx.send(ChannelEvents::RematchRoomError((socket, error))).await.unwrap(),
+                                            }
+                                        });
+                                    }
+                                }
+                              },
+                              Some(PageResults::CancelRematch) => {
+                                self.rematch = None;
+                                if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut()
+                                    && self.rematch_room.take().is_some()
+                                {
+                                    socket.leave_room().await.ok();
+                                }
+                                if let Pages::GameOver(page) = &mut self.current_page {
+                                    page.rematch_cancelled();
+                                }
+                              },
+                              Some(PageResults::ThemeSelected(name)) => {
+                                if let Ok(theme) = self.config.resolve_theme(&name) {
+                                    self.theme = theme.adapt(&self.capabilities);
+                                    self.theme_name = name;
+                                    self.render(terminal, true)?;
+                                }
+                              },
+                              Some(PageResults::KeymapChanged(keymap)) => self.keymap = keymap,
+                              Some(PageResults::SaveSettings(settings)) => {
+                                let result = self.config.save(settings);
+                                self.apply_config();
+                                if let Pages::Settings(page) = &mut self.current_page {
+                                    match result {
+                                        Ok(()) => page.info("Saved, a graphics change applies on the next start".to_string()),
+                                        Err(err) => page.error(err.to_string()),
+                                    }
+                                }
+                                self.render(terminal, true)?;
+                              },
+                              Some(PageResults::ForgetSession) => {
+                                if let (Some(host), Pages::GameModeSelector(page)) = (&self.host, &mut self.current_page) {
+                                    match self.sessions.remove(host.as_str()) {
+                                        Some(_) => match self.sessions.save() {
+                                            Ok(()) => page.info("Session forgotten, the next start asks for a login".to_string()),
+                                            Err(err) => page.error(err.to_string()),
+                                        },
+                                        None => page.info("No session saved for this host".to_string()),
+                                    }
+                                }
+                              },
+                              Some(PageResults::Logout) => self.logout().await,
+                              Some(PageResults::Exit) => {
+                                // A session that is not saved can't be used again, so log it out
+                                if let (Some(host), Some(_)) = (&self.host, &self.auth_token)
+                                    && self.sessions.get(host.as_str()).is_none()
+                                {
+                                    time::timeout(LOGOUT_TIMEOUT, auth::logout(&self.api())).await.ok();
+                                }
+                                break Ok(());
+                              },
+                              _ => (),
                           }
                           self.render(terminal, false)?;
                       },
@@ -376,19 +694,64 @@ impl App {
 
                 Some(msg) = rx.recv() => {
                     match (msg, &mut self.current_page) {
-                        (ChannelEvents::LoginSuccess((host, token)), _) => {
-                            self.auth_token = Some(token);
-                            self.host = Some(host);
-                            self.current_page = Pages::GameModeSelector(GameModePage::new());
+                        (ChannelEvents::SessionValid((host, token, refresh_token)), Pages::HostSelector(_) | Pages::AccountSwitcher(_)) => {
+                            self.set_host(host);
+                            self.set_tokens(token, refresh_token);
+                            self.logged_in(&tx);
+                        }
+                        (ChannelEvents::SessionInvalid((host, session, login_type)), Pages::HostSelector(page)) => {
+                            page.host_info(None);
+                            self.sessions.forget(host.as_str(), &session);
+                            if let Err(err) = self.sessions.save() {
+                                page.host_error(&LoginErrors::Unknown(err.to_string()));
+                            } else if let Some(login_type) = login_type {
+                                self.start_login(host, login_type, &tx).await;
+                            }
+                        }
+                        (ChannelEvents::SessionInvalid((host, session, _)), Pages::AccountSwitcher(page)) => {
+                            self.sessions.forget(host.as_str(), &session);
+                            page.set_accounts(self.sessions.accounts(host.as_str()));
+                            match self.sessions.save() {
+                                Ok(()) => page.error(format!("The session of {} expired, log in again", session.label())),
+                                Err(err) => page.error(err.to_string()),
+                            }
+                        }
+                        (ChannelEvents::SessionError(error), Pages::HostSelector(page)) => {
+                            page.host_error(&error);
+                        }
+                        (ChannelEvents::SessionError(error), Pages::AccountSwitcher(page)) => {
+                            page.error(error.to_string());
+                        }
+                        (ChannelEvents::HostHealth((url, health)), Pages::HostSelector(page)) => {
+                            page.set_health(url, health);
+                        }
+                        (ChannelEvents::LoginSuccess((host, token, refresh_token)), _) => {
+                            self.set_host(host);
+                            self.set_tokens(token, refresh_token);
+                            self.logged_in(&tx);
                         }
                         (ChannelEvents::TotpRequired((host, token)), _) => {
                           self.auth_token = Some(token);
-                          self.host = Some(host);
+                          self.set_host(host);
                           self.current_page = Pages::TotpPage(TotpPage::new());
                         }
-                        (ChannelEvents::TotpSuccess(token), _) => {
-                          self.auth_token = Some(token);
-                          self.current_page = Pages::GameModeSelector(GameModePage::new());
+                        (ChannelEvents::TotpSuccess((token, refresh_token)), _) => {
+                          self.set_tokens(token, refresh_token);
+                          self.logged_in(&tx);
+                        }
+                        (ChannelEvents::TokenRefreshed((spent, session)), _) => {
+                            match self.refresh_token.as_deref() == Some(spent.as_str()) {
+                                true => {
+                                    self.set_tokens(session.token, session.refresh_token);
+                                    self.store_session().ok();
+                                }
+                                // The account was switched while the refresh ran
+                                false => {
+                                    if self.sessions.renew(&spent, session) {
+                                        self.sessions.save().ok();
+                                    }
+                                }
+                            }
                         }
                         (ChannelEvents::TotpError(error), Pages::TotpPage(page)) => {
                           page.totp_error(&error);
@@ -396,99 +759,845 @@ impl App {
                         (ChannelEvents::LoginError(error), Pages::Login(page)) => {
                             page.login_error(&error);
                         },
+                        (ChannelEvents::SignupError(error), Pages::Signup(page)) => {
+                            page.signup_error(&error);
+                        },
+                        (ChannelEvents::AccountLoaded(account), Pages::Security(page)) => {
+                            page.set_account(account);
+                        },
+                        (ChannelEvents::AccountError(error), Pages::Security(page)) => {
+                            page.error(error.to_string());
+                        },
+                        (ChannelEvents::AccountLoaded(account), Pages::Account(page)) => {
+                            page.set_account(account);
+                        },
+                        (ChannelEvents::ProfileLoaded(profile), Pages::Account(page)) => {
+                            page.set_profile(profile);
+                        },
+                        (ChannelEvents::AccountError(error) | ChannelEvents::AccountRequestError(error), Pages::Account(page)) => {
+                            page.error(error.to_string());
+                        },
+                        (ChannelEvents::AccountUpdated((message, token)), Pages::Account(page)) => {
+                            page.done(message);
+                            if let Some(token) = token.filter(|token| !auth::is_preauth(token)) {
+                                self.set_tokens(token, self.refresh_token.clone());
+                                self.store_session().ok();
+                            }
+                            if self.auth_token.is_some() {
+                                tokio::spawn(load_account(self.api(), tx.clone()));
+                                tokio::spawn(load_profile(self.api(), tx.clone()));
+                            }
+                        },
+                        (ChannelEvents::AccountDeleted, Pages::Account(_)) => {
+                            self.logout().await;
+                            if let Pages::HostSelector(page) = &mut self.current_page {
+                                page.host_info(Some("Account deleted".to_string()));
+                            }
+                        },
+                        (ChannelEvents::TotpChanged(enabled), Pages::Security(page)) => {
+                            match enabled {
+                                true => page.info("2FA is on, logins now ask for a code".to_string()),
+                                false => page.info("2FA is off".to_string()),
+                            }
+                        },
+                        (ChannelEvents::TotpChangeError(error), Pages::Security(page)) => {
+                            page.error(error.to_string());
+                        },
                         (ChannelEvents::NicknameError(error), Pages::NicknameSelector(page)) => {
                           page.nickname_error(&error);
                         },
-                        (ChannelEvents::RemoteRedirect(url), Pages::HostSelector(page)) => {
-                          if webbrowser::open(url.as_str()).is_err() {
-                            page.host_error(&LoginErrors::Unknown("Unable to open webbrowser".to_string()));
-                            self.socket = None;
-                          }
+                        (ChannelEvents::RemoteRedirect(url), Pages::RemoteLogin(page)) => {
+                          let opened = self.capabilities.browser && webbrowser::open(url.as_str()).is_ok();
+                          page.set_url(url, opened);
                         },
-                        (ChannelEvents::RemoteRedirectCallback((token, nickname_required)), Pages::HostSelector(_)) => {
-                          self.auth_token = Some(token);
-                          self.socket = None;
-                          if nickname_required {
-                            self.current_page = Pages::NicknameSelector(NicknamePage::new());
-                          } else {
-                            self.current_page = Pages::GameModeSelector(GameModePage::new());
-                          }
+                        (ChannelEvents::RemoteRedirectCallback((token, nickname_required)), Pages::RemoteLogin(_)) => {
+                          self.remote_logged_in(token, nickname_required, &tx);
                         },
-                        (ChannelEvents::RemoteRedirectError(error), Pages::HostSelector(page)) => {
-                          page.host_error(&error);
-                          self.socket = None;
+                        (ChannelEvents::RemoteRedirectError(error), Pages::RemoteLogin(page)) => {
+                          // Without a URL there is nothing to wait for
+                          match page.has_url() {
+                            true => page.error(error.to_string()),
+                            false => self.cancel_remote_login(Some(&error)),
+                          }
                         }
                         (ChannelEvents::RoomCreated((client, room_id)), Pages::GameModeSelector(_)) => {
-                            self.socket = Some(WsOrWeb::Websocket(client));
+                            self.socket = Some(WsOrWeb::Websocket(Box::new(client)));
                             self.current_page = Pages::GameLobby(GameLobbyPage::new(room_id));
                         },
                         (ChannelEvents::RoomJoined(client), _) => {
-                            self.socket = Some(WsOrWeb::Websocket(client));
+                            self.socket = Some(WsOrWeb::Websocket(Box::new(client)));
                         },
+                        (ChannelEvents::RoomJoinError(EventError::InvalidCredentials), _) => {
+                            self.session_expired();
+                        }
                         (ChannelEvents::RoomJoinError(error), Pages::JoinRoom(page)) => {
                             page.join_error(&error);
                         }
+                        (ChannelEvents::RematchRoomCreated((mut socket, room_id)), Pages::GameOver(page)) => {
+                            if !page.is_waiting_for_rematch() {
+                                // Cancelled while the room was being created
+                                socket.leave_room().await.ok();
+                            } else if !self.singleplayer {
+                                match (page.opponent().id.as_id(), self.host.as_ref(), self.auth_token.as_ref()) {
+                                    (Some(opponent_id), Some(host), Some(token)) => {
+                                        let (cancel, cancelled) = oneshot::channel();
+                                        page.rematch_waiting(format!("Inviting {}", page.opponent().nickname));
+                                        tokio::spawn(invite_to_rematch(
+                                            host.socket("livechat/", token),
+                                            token.clone(),
+                                            opponent_id,
+                                            room_id.clone(),
+                                            tx.clone(),
+                                            cancelled,
+                                        ));
+                                        self.rematch = Some(cancel);
+                                        self.rematch_room = Some(room_id);
+                                    }
+                                    (_, _, _) => {
+                                        socket.leave_room().await.ok();
+                                        page.rematch_error("This opponent can't be invited".to_string());
+                                    }
+                                }
+                            }
+                            self.socket = Some(WsOrWeb::Websocket(socket));
+                        }
+                        (ChannelEvents::RematchRoomError((socket, error)), Pages::GameOver(page)) => {
+                            self.socket = Some(WsOrWeb::Websocket(socket));
+                            page.rematch_error(error.to_string());
+                        }
+                        (ChannelEvents::RematchRoomCreated((mut socket, _)), _) => {
+                            // The game over page was left while the room was being
+                            // created, nobody is going to join it
+                            socket.leave_room().await.ok();
+                            socket.close().await.ok();
+                        }
+                        (ChannelEvents::RematchRoomError((mut socket, _)), _) => {
+                            socket.close().await.ok();
+                        }
+                        (ChannelEvents::RematchWaiting(message), Pages::GameOver(page)) => {
+                            page.rematch_waiting(message);
+                        }
+                        (ChannelEvents::RematchFailed(message), Pages::GameOver(page)) => {
+                            self.rematch = None;
+                            if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut()
+                                && self.rematch_room.take().is_some()
+                            {
+                                socket.leave_room().await.ok();
+                            }
+                            page.rematch_error(message);
+                        }
+                        (ChannelEvents::RematchJoin(room_id), Pages::GameOver(page)) => {
+                            self.rematch = None;
+                            if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut()
+                                && let Err(error) = socket.join_room(room_id).await
+                            {
+                                page.rematch_error(error.to_string());
+                            }
+                        }
                         (_, _) => (),
                     }
                 }
 
+                _ = wait_for_refresh(refresh_at) => {
+                    self.refresh_at = None;
+                    if let (Some(host), Some(refresh_token)) = (&self.host, self.refresh_token.clone()) {
+                        let api = self.api.for_host(host);
+                        let tx = tx.clone();
+                        tokio::spawn(async move {
+                            if let Ok(response) = auth::refresh(&api, &refresh_token).await {
+                                let session = Session::new(response.token, Some(response.refresh_token));
+                                tx.send(ChannelEvents::TokenRefreshed((refresh_token, session))).await.ok();
+                            }
+                        });
+                    }
+                }
+
                 _ = interval.tick() => {
-                    if let (false, Pages::Game(game), Some(WsOrWeb::Websocket(socket))) = (self.kitty_protocol_support, &mut self.current_page, self.socket.as_mut()) {
-                        if game.tick(socket).await.is_err() {
-                            self.abort_game().await;
+                    if let (Pages::Game(game), Some(WsOrWeb::Websocket(socket))) = (&mut self.current_page, self.socket.as_mut())
+                        && game.tick(socket, self.capabilities.kitty_keyboard).await.is_err() {
+                        self.abort_game().await;
+                    }
+                    if let Pages::RemoteLogin(page) = &self.current_page
+                        && page.expired()
+                    {
+                        self.cancel_remote_login(Some(&LoginErrors::Unknown("The 42 login timed out".to_string())));
+                    }
+                    if let Pages::HostSelector(page) = &mut self.current_page {
+                        for url in page.stale_health() {
+                            if let Ok(host) = Server::parse(&url) {
+                                tokio::spawn(check_health(self.api.for_host(&host), url, tx.clone()));
+                            }
                         }
                     }
                     self.render(terminal, false)?;
+                    if interval.period() != self.config.tick() {
+                        interval = time::interval(self.config.tick());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Continues with the saved account of the given or last host, or lists
+    /// them when there are several.
+    fn restore_session(&mut self, tx: &Sender<ChannelEvents>) {
+        let host = match self.host.take() {
+            Some(host) => host,
+            None => match self.sessions.last_host.as_deref().map(Server::parse) {
+                Some(Ok(host)) if !self.sessions.accounts(host.as_str()).is_empty() => host,
+                _ => return,
+            },
+        };
+
+        let mut accounts = self.sessions.accounts(host.as_str());
+        if accounts.len() > 1 {
+            self.set_host(host.clone());
+            return self.show_accounts(&host);
+        }
+        let mut page = HostSelectorPage::with_host(host.as_str(), self.hosts.list());
+        if let Some(session) = accounts.pop() {
+            page.host_info(Some(format!("Restoring session for {}", host)));
+            tokio::spawn(verify_session(self.api.for_host(&host), host, session, None, tx.clone()));
+        }
+        self.current_page = Pages::HostSelector(page);
+    }
+
+    fn show_accounts(&mut self, host: &Server) {
+        let active = self
+            .auth_token
+            .clone()
+            .or_else(|| self.sessions.get(host.as_str()).map(|session| session.token.clone()));
+        let page = AccountSwitcherPage::new(host.to_string(), self.sessions.accounts(host.as_str()), active);
+        self.current_page = Pages::AccountSwitcher(page);
+    }
+
+    async fn account_choice(&mut self, choice: AccountChoice, tx: &Sender<ChannelEvents>) {
+        let Some(host) = self.host.clone() else {
+            return;
+        };
+        match choice {
+            AccountChoice::Use(session) if self.auth_token.as_ref() == Some(&session.token) => {
+                self.current_page = Pages::GameModeSelector(GameModePage::new(self.account_nickname()));
+            }
+            AccountChoice::Use(session) => {
+                if let Pages::AccountSwitcher(page) = &mut self.current_page {
+                    page.info(format!("Switching to {}", session.label()));
                 }
+                tokio::spawn(verify_session(self.api.for_host(&host), host, session, None, tx.clone()));
+            }
+            AccountChoice::Add => {
+                let login_type = self
+                    .hosts
+                    .list()
+                    .iter()
+                    .find(|entry| entry.url == host.as_str())
+                    .map(|entry| entry.login.login_type())
+                    .unwrap_or(LoginType::LocalLogin);
+                self.start_login(host, login_type, tx).await;
+            }
+            AccountChoice::Forget(session) => {
+                self.sessions.forget(host.as_str(), &session);
+                let result = self.sessions.save();
+                if let Pages::AccountSwitcher(page) = &mut self.current_page {
+                    page.set_accounts(self.sessions.accounts(host.as_str()));
+                    match result {
+                        Ok(()) => page.info(format!("{} forgotten", session.label())),
+                        Err(err) => page.error(err.to_string()),
+                    }
+                }
+            }
+            AccountChoice::Back if self.auth_token.is_some() => {
+                self.current_page = Pages::GameModeSelector(GameModePage::new(self.account_nickname()));
+            }
+            AccountChoice::Back => {
+                self.current_page = Pages::HostSelector(HostSelectorPage::with_host(host.as_str(), self.hosts.list()));
             }
         }
     }
 
+    /// Nickname of the account in use, for the indicators of the menus and
+    /// the game.
+    fn account_nickname(&self) -> Option<String> {
+        self.auth_token.as_deref().and_then(auth::token_nickname)
+    }
+
     fn render(
         &mut self,
         terminal: &mut DefaultTerminal,
         force_redraw: bool,
     ) -> Result<(), FatalErrors> {
+        if self.image_on_screen
+            && !matches!(self.current_page, Pages::Game(_))
+            && let Some(protocol) = self.capabilities.graphics
+        {
+            terminal
+                .backend_mut()
+                .write_all(graphics::clear_sequence(protocol))
+                .map_err(|_| FatalErrors::RenderingError)?;
+            terminal.clear().map_err(|_| FatalErrors::RenderingError)?;
+            self.image_on_screen = false;
+        }
+
+        if force_redraw && let Pages::Game(game) = &mut self.current_page {
+            game.forget_image();
+        }
+
         if force_redraw || self.current_page.needs_update() {
             terminal
-                .draw(|frame| self.current_page.render(frame))
+                .draw(|frame| self.current_page.render(frame, &self.theme))
                 .map_err(|_| FatalErrors::RenderingError)?;
         }
 
+        if let Pages::Game(game) = &mut self.current_page {
+            let output = game.take_output();
+            if !output.is_empty() {
+                let backend = terminal.backend_mut();
+                backend
+                    .write_all(&output)
+                    .and_then(|_| backend.flush())
+                    .map_err(|_| FatalErrors::RenderingError)?;
+                self.image_on_screen |= self.capabilities.graphics.is_some();
+            }
+        }
+
         Ok(())
     }
 
+    fn choose_mode(&mut self, mode: GameModes, tx: &Sender<ChannelEvents>) {
+        self.singleplayer = mode == GameModes::SinglePlayer;
+        match (mode, self.host.as_ref(), self.auth_token.as_ref()) {
+            (GameModes::SinglePlayer, Some(host), Some(token)) => {
+                let host = host.clone();
+                let token = token.clone();
+                let refresh_token = self.refresh_token.clone();
+                let api = self.api();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let connect = |token: String| {
+                        let endpoint = host.socket("", &token);
+                        async move { create_singleplayer_game(&endpoint, &token).await }
+                    };
+                    match with_refresh(&api, token, refresh_token, &tx, connect).await {
+                        Ok((client, _)) => tx.send(ChannelEvents::RoomJoined(client)).await.unwrap(),
+                        Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
+                    }
+                });
+            }
+            (GameModes::CreateRoom, Some(host), Some(token)) => {
+                let host = host.clone();
+                let token = token.clone();
+                let refresh_token = self.refresh_token.clone();
+                let api = self.api();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let connect = |token: String| {
+                        let endpoint = host.socket("", &token);
+                        async move { create_join_room(&endpoint, &token, None).await }
+                    };
+                    match with_refresh(&api, token, refresh_token, &tx, connect).await {
+                        Ok((client, Some(room_id))) => tx.send(ChannelEvents::RoomCreated((client, room_id))).await.unwrap(),
+                        Ok((_, None)) => panic!("create_join_room returned no room_id after creating a room"),
+                        Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
+                    }
+                });
+            },
+            (GameModes::JoinRoom, _, _) => self.current_page = Pages::JoinRoom(JoinRoomPage::new()),
+            (GameModes::Account, Some(_), Some(_)) => {
+                tokio::spawn(load_account(self.api(), tx.clone()));
+                tokio::spawn(load_profile(self.api(), tx.clone()));
+                self.current_page = Pages::Account(AccountPage::new());
+            },
+            (GameModes::Security, Some(_), Some(_)) => {
+                tokio::spawn(load_account(self.api(), tx.clone()));
+                self.current_page = Pages::Security(SecurityPage::new());
+            },
+            (GameModes::SwitchAccount, Some(host), Some(_)) => self.show_accounts(&host.clone()),
+            (GameModes::Settings, _, _) => {
+                let path = Config::path()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or("config.toml".to_string());
+                self.current_page = Pages::Settings(SettingsPage::new(self.config.settings(), self.config.theme_names(), path));
+            },
+            (_, _, _) => (),
+        }
+    }
+
+    fn join_room(&mut self, room_id: String, tx: &Sender<ChannelEvents>) {
+        let host = self.host.clone();
+        let auth_token = self.auth_token.clone();
+        let refresh_token = self.refresh_token.clone();
+        let api = self.api();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let (Some(host), Some(token)) = (host, auth_token) {
+                let connect = |token: String| {
+                    let endpoint = host.socket("", &token);
+                    let room_id = room_id.clone();
+                    async move { create_join_room(&endpoint, &token, Some(room_id)).await }
+                };
+                match with_refresh(&api, token, refresh_token, &tx, connect).await {
+                    Ok((client, _)) => tx.send(ChannelEvents::RoomJoined(client)).await.unwrap(),
+                    Err(error) => tx.send(ChannelEvents::RoomJoinError(error)).await.unwrap(),
+                }
+            }
+        });
+    }
+
+    async fn start_login(&mut self, host: Server, login_type: LoginType, tx: &Sender<ChannelEvents>) {
+        match login_type {
+            LoginType::LocalLogin => {
+                self.current_page = Pages::Login(LoginPage::new());
+            }
+            LoginType::Signup => {
+                self.current_page = Pages::Signup(SignupPage::new());
+            }
+            LoginType::RemoteLogin => {
+                let addr: SocketAddr = ([127, 0, 0, 1], self.config.callback_port()).into();
+                let listener = match TcpListener::bind(addr).await {
+                    Ok(listener) => listener,
+                    Err(err) => return self.host_error(&format!("Unable to listen on {}: {}", addr, err)),
+                };
+                let port = match listener.local_addr() {
+                    Ok(addr) => addr.port(),
+                    Err(_) => return self.host_error("Unable to fetch Port from local webserver"),
+                };
+                let state = auth::callback_state();
+                self.socket = Some(WsOrWeb::Webserver(CallbackListener {
+                    listener,
+                    state: Arc::new(CallbackState {
+                        expected: state.clone(),
+                        done: AtomicBool::new(false),
+                        origin: host.origin(),
+                    }),
+                }));
+                self.current_page = Pages::RemoteLogin(RemoteLoginPage::new(port, state.clone()));
+
+                let api = self.api.for_host(&host);
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match auth::remotelogin(&api, port, &state).await {
+                        Ok(response) => tx.send(ChannelEvents::RemoteRedirect(response.url)).await.ok(),
+                        Err(err) => tx.send(ChannelEvents::RemoteRedirectError(err)).await.ok(),
+                    };
+                });
+            }
+        }
+    }
+
+    /// Client for the selected host, authenticated once logged in.
+    fn api(&self) -> ApiClient {
+        let api = match &self.host {
+            Some(host) => self.api.for_host(host),
+            None => self.api.clone(),
+        };
+        match &self.auth_token {
+            Some(token) => api.with_token(token),
+            None => api,
+        }
+    }
+
+    /// A 42 login completed through the listener or a pasted callback.
+    fn remote_logged_in(&mut self, token: String, nickname_required: bool, tx: &Sender<ChannelEvents>) {
+        // The web page only passes the access token on
+        self.set_tokens(token, None);
+        self.socket = None;
+        if nickname_required {
+            self.current_page = Pages::NicknameSelector(NicknamePage::new());
+        } else {
+            self.logged_in(tx);
+        }
+    }
+
+    /// Stops waiting for the 42 login and goes back to the host selector.
+    fn cancel_remote_login(&mut self, error: Option<&LoginErrors>) {
+        self.socket = None;
+        let host = self.host.as_ref().map(Server::as_str).unwrap_or_default();
+        let mut page = HostSelectorPage::with_host(host, self.hosts.list());
+        if let Some(error) = error {
+            page.host_error(error);
+        }
+        self.current_page = Pages::HostSelector(page);
+    }
+
+    fn host_error(&mut self, message: &str) {
+        if let Pages::HostSelector(page) = &mut self.current_page {
+            page.host_error(&LoginErrors::Unknown(message.to_string()));
+        }
+    }
+
+    /// Switches to `host`, its profile of the config applies from now on.
+    fn set_host(&mut self, host: Server) {
+        self.config.select_host(&host);
+        self.host = Some(host);
+        self.apply_config();
+    }
+
+    /// Takes over theme and keymap after the host or the config changed.
+    fn apply_config(&mut self) {
+        if let Ok(theme) = self.config.theme() {
+            self.theme = theme.adapt(&self.capabilities);
+            self.theme_name = self.config.theme_name().to_string();
+        }
+        if let Ok(keymap) = self.config.keymap() {
+            self.keymap = keymap;
+        }
+    }
+
+    /// Switches to the mode selector once fully logged in, keeping the session
+    /// for the next start unless that is turned off.
+    fn logged_in(&mut self, tx: &Sender<ChannelEvents>) {
+        let mut page = GameModePage::new(self.account_nickname());
+        if let Err(err) = self.store_session() {
+            page.error(err.to_string());
+        }
+        self.current_page = Pages::GameModeSelector(page);
+
+        match self.start.take() {
+            Some(Start::Play { single: true }) => self.choose_mode(GameModes::SinglePlayer, tx),
+            Some(Start::Play { single: false }) => self.choose_mode(GameModes::CreateRoom, tx),
+            Some(Start::Join(room_id)) => {
+                self.current_page = Pages::JoinRoom(JoinRoomPage::with_room(&room_id));
+                self.join_room(room_id, tx);
+            }
+            _ => (),
+        }
+    }
+
+    fn store_session(&mut self) -> Result<(), SessionErrors> {
+        match (self.config.remember_session(), &self.host, &self.auth_token) {
+            (true, Some(host), Some(token)) => {
+                self.sessions.insert(host.as_str(), token, self.refresh_token.as_deref());
+                self.sessions.save()
+            }
+            (_, _, _) => Ok(()),
+        }
+    }
+
+    /// Sets the tokens of a completed login and schedules the refresh of the
+    /// access token shortly before it expires, if the server handed out a
+    /// refresh token.
+    fn set_tokens(&mut self, token: String, refresh_token: Option<String>) {
+        self.refresh_at = match (&refresh_token, auth::token_expiry(&token)) {
+            (Some(_), Some(expiry)) => {
+                let remaining = expiry
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default()
+                    .saturating_sub(REFRESH_MARGIN);
+                Some(Instant::now() + remaining)
+            }
+            (_, _) => None,
+        };
+        self.auth_token = Some(token);
+        self.refresh_token = refresh_token;
+    }
+
+    /// The server rejected the token and it could not be refreshed, so the
+    /// user has to log in again.
+    fn session_expired(&mut self) {
+        if let Some(host) = self.host.as_ref() {
+            self.sessions.remove(host.as_str());
+            self.sessions.save().ok();
+        }
+        let mut page = HostSelectorPage::with_host(self.host.as_ref().map(Server::as_str).unwrap_or_default(), self.hosts.list());
+        page.host_error(&LoginErrors::Unknown(EventError::InvalidCredentials.to_string()));
+        self.auth_token = None;
+        self.refresh_token = None;
+        self.refresh_at = None;
+        self.current_page = Pages::HostSelector(page);
+    }
+
+    /// Tells the server about the logout and forgets everything about the
+    /// session locally. The server doesn't revoke the token yet.
+    async fn logout(&mut self) {
+        if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut() {
+            socket.close().await.ok();
+        }
+        self.socket = None;
+        self.rematch = None;
+        self.rematch_room = None;
+
+        if self.auth_token.is_some() {
+            let api = self.api();
+            tokio::spawn(async move { auth::logout(&api).await.ok() });
+        }
+        let host = self.host.as_ref().map(Server::as_str).unwrap_or_default();
+        self.auth_token = None;
+        self.refresh_token = None;
+        self.refresh_at = None;
+
+        let mut page = HostSelectorPage::with_host(host, self.hosts.list());
+        self.sessions.remove(host);
+        match self.sessions.save() {
+            Ok(()) => page.host_info(Some("Logged out".to_string())),
+            Err(err) => page.host_error(&LoginErrors::Unknown(err.to_string())),
+        }
+        self.current_page = Pages::HostSelector(page);
+    }
+
     async fn abort_game(&mut self) {
         if let Some(WsOrWeb::Websocket(socket)) = self.socket.as_mut() {
             socket.close().await.ok();
         }
         self.socket = None;
-        self.current_page = Pages::GameModeSelector(GameModePage::new());
+        self.rematch = None;
+        self.rematch_room = None;
+        self.current_page = Pages::GameModeSelector(GameModePage::new(self.account_nickname()));
+    }
+}
+
+/// OSC 52 sequence that sets the clipboard of the terminal, which also works
+/// over SSH.
+fn clipboard_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+async fn check_health(api: ApiClient, url: String, tx: Sender<ChannelEvents>) {
+    let health = hosts::health(&api).await;
+    tx.send(ChannelEvents::HostHealth((url, health))).await.ok();
+}
+
+async fn load_account(api: ApiClient, tx: Sender<ChannelEvents>) {
+    let event = match auth::account(&api).await {
+        Ok(account) => ChannelEvents::AccountLoaded(account),
+        Err(error) => ChannelEvents::AccountError(error),
+    };
+    tx.send(event).await.ok();
+}
+
+async fn load_profile(api: ApiClient, tx: Sender<ChannelEvents>) {
+    let event = match auth::profile(&api).await {
+        Ok(profile) => ChannelEvents::ProfileLoaded(profile),
+        Err(error) => ChannelEvents::AccountError(error),
+    };
+    tx.send(event).await.ok();
+}
+
+/// Confirms the password of a local account by logging in with it, the
+/// token of that login is thrown away.
+async fn reauthenticate(api: &ApiClient, reauth: Option<(String, String)>) -> Result<(), LoginErrors> {
+    match reauth {
+        Some((email, password)) => auth::login(api, &email, &password).await.map(|_| ()),
+        None => Ok(()),
     }
 }
 
-fn get_endpoint(host: &str, token: &str) -> String {
-    if cfg!(debug_assertions) {
-        format!(
-            "wss://{}:3000/socket.io/?token={}&EIO=4&transport=websocket",
-            host, token
-        )
-    } else {
-        format!(
-            "wss://{}:8443/socket.io/?token={}&EIO=4&transport=websocket",
-            host, token
-        )
+async fn account_request(api: ApiClient, request: AccountRequest, tx: Sender<ChannelEvents>) {
+    let result = match request {
+        AccountRequest::Nickname { nickname, reauth } => match reauthenticate(&api, reauth).await {
+            Ok(()) => auth::update_nickname(&api, &nickname)
+                .await
+                .map(|response| ChannelEvents::AccountUpdated((format!("Nickname changed to {}", nickname), response.token))),
+            Err(error) => Err(error),
+        },
+        AccountRequest::Email { email, password } => auth::update_account(&api, &email, &password, None)
+            .await
+            .map(|_| ChannelEvents::AccountUpdated((format!("E-Mail changed to {}", email), None))),
+        AccountRequest::Password { email, password, new_password } => {
+            auth::update_account(&api, &email, &password, Some(&new_password))
+                .await
+                .map(|_| ChannelEvents::AccountUpdated(("Password changed".to_string(), None)))
+        }
+        // The server checks the password and the 2FA code itself
+        AccountRequest::Delete { password, totp_code } => {
+            auth::delete_account(&api, password.as_deref(), totp_code.as_deref())
+                .await
+                .map(|_| ChannelEvents::AccountDeleted)
+        }
+    };
+
+    let event = result.unwrap_or_else(ChannelEvents::AccountRequestError);
+    tx.send(event).await.ok();
+}
+
+/// Checks a saved session and refreshes it once if it was rejected,
+/// `login_type` is the login to fall back to when the session expired.
+async fn verify_session(
+    api: ApiClient,
+    host: Server,
+    session: Session,
+    login_type: Option<LoginType>,
+    tx: Sender<ChannelEvents>,
+) {
+    let event = match auth::resume(&api, &session).await {
+        Ok(Some(session)) => ChannelEvents::SessionValid((host, session.token, session.refresh_token)),
+        Ok(None) => ChannelEvents::SessionInvalid((host, session, login_type)),
+        Err(error) => ChannelEvents::SessionError(error),
+    };
+    tx.send(event).await.ok();
+}
+
+/// Runs `connect` and, when the server rejects the token, refreshes it and
+/// tries once more. The App learns about the new token through the channel.
+async fn with_refresh<T, F, Fut>(
+    api: &ApiClient,
+    token: String,
+    refresh_token: Option<String>,
+    tx: &Sender<ChannelEvents>,
+    connect: F,
+) -> Result<T, EventError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, EventError>>,
+{
+    match (connect(token).await, refresh_token) {
+        (Err(EventError::InvalidCredentials), Some(refresh_token)) => {
+            let response = auth::refresh(api, &refresh_token)
+                .await
+                .map_err(|_| EventError::InvalidCredentials)?;
+            let session = Session::new(response.token.clone(), Some(response.refresh_token));
+            tx.send(ChannelEvents::TokenRefreshed((refresh_token, session)))
+                .await
+                .ok();
+            connect(response.token).await
+        }
+        (result, _) => result,
     }
 }
 
+async fn wait_for_refresh(at: Option<Instant>) {
+    match at {
+        Some(at) => time::sleep_until(at).await,
+        None => future::pending().await,
+    }
+}
+
+async fn create_rematch_room(
+    socket: &mut SocketIoClient,
+    singleplayer: bool,
+) -> Result<String, EventError> {
+    let request = match singleplayer {
+        true => CreateRoomRequest::singleplayer(),
+        false => CreateRoomRequest::multiplayer(),
+    };
+
+    for _ in 1..REMATCH_ROOM_ATTEMPTS {
+        match socket.create_room(request.clone()).await {
+            Err(EventError::CreateRoomError) => time::sleep(REMATCH_RETRY_DELAY).await,
+            result => return result,
+        }
+    }
+    socket.create_room(request).await
+}
+
+/// Invites the opponent through the live chat and keeps the invitation open
+/// until the rematch starts or is cancelled, which drops `cancel`.
+async fn invite_to_rematch(
+    endpoint: String,
+    token: String,
+    opponent_id: u32,
+    room_id: String,
+    tx: Sender<ChannelEvents>,
+    mut cancel: oneshot::Receiver<()>,
+) {
+    let mut live_chat = match LiveChatClient::new(&endpoint, &token).await {
+        Ok(live_chat) => live_chat,
+        Err(error) => {
+            tx.send(ChannelEvents::RematchFailed(error.to_string())).await.ok();
+            return;
+        }
+    };
+
+    loop {
+        let message = match live_chat.invite(opponent_id).await {
+            Ok(InvitationStatus::Offline) => "Opponent is offline, retrying",
+            Ok(InvitationStatus::Refused) => {
+                let message = "Invitation refused, rematches need you to be friends".to_string();
+                tx.send(ChannelEvents::RematchFailed(message)).await.ok();
+                live_chat.close().await.ok();
+                return;
+            }
+            Ok(_) => break,
+            Err(error) => {
+                tx.send(ChannelEvents::RematchFailed(error.to_string())).await.ok();
+                return;
+            }
+        };
+
+        tx.send(ChannelEvents::RematchWaiting(message.to_string())).await.ok();
+        select! {
+            _ = &mut cancel => {
+                live_chat.close().await.ok();
+                return;
+            },
+            _ = time::sleep(REMATCH_RETRY_DELAY) => (),
+        }
+    }
+
+    if live_chat.room_id_created(&room_id, opponent_id).await.is_err() {
+        tx.send(ChannelEvents::RematchFailed("Unable to send the room to the opponent".to_string()))
+            .await
+            .ok();
+        return;
+    }
+    tx.send(ChannelEvents::RematchWaiting(format!("Invitation sent for room {}", room_id)))
+        .await
+        .ok();
+
+    loop {
+        select! {
+            _ = &mut cancel => break,
+            event = live_chat.wait_for_events() => match event {
+                Ok(LiveChatEvents::CanYouPlay(from_id)) => {
+                    live_chat.status_response("available", from_id).await.ok();
+                },
+                Ok(LiveChatEvents::InvitationDeclined(from_id)) if from_id == opponent_id => {
+                    tx.send(ChannelEvents::RematchFailed("Opponent declined the rematch".to_string())).await.ok();
+                    break;
+                },
+                Ok(_) => (),
+                Err(_) => break,
+            },
+        }
+    }
+
+    live_chat.delete_invitation(opponent_id, "sent").await.ok();
+    live_chat.close().await.ok();
+}
+
+/// Waits on the live chat for the opponent to send the room of the rematch.
+async fn wait_for_rematch(
+    endpoint: String,
+    token: String,
+    tx: Sender<ChannelEvents>,
+    mut cancel: oneshot::Receiver<()>,
+) {
+    let mut live_chat = match LiveChatClient::new(&endpoint, &token).await {
+        Ok(live_chat) => live_chat,
+        Err(error) => {
+            tx.send(ChannelEvents::RematchFailed(error.to_string())).await.ok();
+            return;
+        }
+    };
+
+    loop {
+        select! {
+            _ = &mut cancel => break,
+            event = live_chat.wait_for_events() => match event {
+                Ok(LiveChatEvents::JoinTheRoom(room_id)) => {
+                    tx.send(ChannelEvents::RematchJoin(room_id)).await.ok();
+                    break;
+                },
+                Ok(LiveChatEvents::CanYouPlay(from_id)) => {
+                    live_chat.status_response("available", from_id).await.ok();
+                },
+                Ok(_) => (),
+                Err(error) => {
+                    tx.send(ChannelEvents::RematchFailed(error.to_string())).await.ok();
+                    break;
+                },
+            },
+        }
+    }
+
+    live_chat.close().await.ok();
+}
+
 async fn create_join_room(
     endpoint: &str,
     token: &str,
     room_id: Option<String>,
 ) -> Result<(SocketIoClient, Option<String>), EventError> {
-    let mut socket = SocketIoClient::new(endpoint, token)
-        .await
-        .map_err(|_| EventError::ConnectionError)?;
+    let mut socket = SocketIoClient::new(endpoint, token).await?;
 
     let room_id = match room_id {
         Some(room_id) => {
@@ -504,9 +1613,7 @@ async fn create_singleplayer_game(
     endpoint: &str,
     token: &str,
 ) -> Result<(SocketIoClient, Option<String>), EventError> {
-    let mut socket = SocketIoClient::new(endpoint, token)
-        .await
-        .map_err(|_| EventError::ConnectionError)?;
+    let mut socket = SocketIoClient::new(endpoint, token).await?;
 
     let room_id = socket
         .create_room(CreateRoomRequest::singleplayer())
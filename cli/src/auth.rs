@@ -1,82 +1,60 @@
-use serde::{Deserialize, Serialize};
-use std::{error::Error, fmt::Display};
-
-#[derive(Serialize)]
-struct LoginRequest<'a> {
-    email: &'a str,
-    password: &'a str,
-}
-
-#[derive(Serialize)]
-struct SetNicknameRequest<'a> {
-    nickname: &'a str,
-}
-
-#[derive(Serialize)]
-struct TotpRequest<'a> {
-    totp_code: &'a str,
-}
-
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
-pub enum BoolOrString {
-    Bool(bool),
-    String(String),
-}
-
-#[derive(Deserialize, Debug)]
-pub struct LoginResponse {
-    success: bool,
-    pub token: String,
-    pub action_required: BoolOrString,
-    pub user: Option<User>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct RedirectResponse {
-    pub url: String,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct NicknameResponse {
-    pub success: bool,
-    pub token: Option<String>,
-    pub error: Option<String>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct User {
-    pub id: usize,
-    pub nickname: String,
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::{Rng, distr::Alphanumeric};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    api::{
+        ApiClient, ApiError,
+        endpoints::{
+            self, AccountResponse, BoolOrString, LoginResponse, NicknameResponse, ProfileResponse,
+            RedirectResponse, RefreshResponse, UpdateProfileResponse,
+        },
+    },
+    credentials::Credentials,
+    session::Session,
+};
+
+#[derive(Deserialize)]
+struct TokenClaims {
+    exp: Option<u64>,
+    sub: Option<String>,
+    nickname: Option<String>,
+    #[serde(default)]
+    nickname_required: bool,
+    #[serde(default)]
+    totp_required: bool,
 }
 
 #[derive(Debug)]
 pub enum LoginErrors {
-    ConnectionError,
-    InvalidResponse,
+    Api(ApiError),
     InvalidCredentials,
     NicknameMissing,
-    ServerError,
+    EmailTaken,
+    Rejected(String),
     Unknown(String),
 }
 
 #[derive(Debug)]
 pub enum TotpErrors {
-    ConnectionError,
-    InvalidResponse,
+    Api(ApiError),
     InvalidTotp,
-    ServerError,
     Unknown(String),
 }
 
 impl Display for LoginErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::ConnectionError => write!(f, "Connection Error"),
-            Self::InvalidResponse => write!(f, "Invalid response received from server"),
+            Self::Api(err) => write!(f, "{}", err),
             Self::InvalidCredentials => write!(f, "Incorrect email or password"),
-            Self::ServerError => write!(f, "Internal Server Error"),
             Self::NicknameMissing => write!(f, "Please set a Nickname on the website first"),
+            Self::EmailTaken => write!(f, "This e-mail is already registered"),
+            Self::Rejected(err) => write!(f, "{}", err),
             Self::Unknown(err) => write!(f, "Error: {}", err),
         }
     }
@@ -84,13 +62,17 @@ impl Display for LoginErrors {
 
 impl Error for LoginErrors {}
 
+impl From<ApiError> for LoginErrors {
+    fn from(err: ApiError) -> Self {
+        Self::Api(err)
+    }
+}
+
 impl Display for TotpErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::ConnectionError => write!(f, "Connection Error"),
-            Self::InvalidResponse => write!(f, "Invalid response received from server"),
+            Self::Api(err) => write!(f, "{}", err),
             Self::InvalidTotp => write!(f, "Incorrect 2FA code"),
-            Self::ServerError => write!(f, "Internal Server Error"),
             Self::Unknown(err) => write!(f, "Unknown Error: {}", err),
         }
     }
@@ -98,44 +80,25 @@ impl Display for TotpErrors {
 
 impl Error for TotpErrors {}
 
-pub async fn login(host: &str, email: &str, password: &str) -> Result<LoginResponse, LoginErrors> {
-    let body = LoginRequest { email, password };
-
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/134.0.0.0 Safari/537.3")
-        .build()
-        .map_err(|err| LoginErrors::Unknown(err.to_string()))?;
-
-    let endpoint = if cfg!(debug_assertions) {
-        format!("https://{}:3000/api/login", host)
-    } else {
-        format!("https://{}:8443/api/login", host)
-    };
-
-    let response = client
-        .post(endpoint)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|_| LoginErrors::ConnectionError)?;
-
-    if response.status().is_server_error() {
-        return Err(LoginErrors::ServerError);
-    } else if response.status().is_client_error() {
-        return Err(LoginErrors::InvalidCredentials);
+impl From<ApiError> for TotpErrors {
+    fn from(err: ApiError) -> Self {
+        Self::Api(err)
     }
+}
 
-    let response_body: LoginResponse = response
-        .json()
-        .await
-        .map_err(|_| LoginErrors::InvalidResponse)?;
+pub async fn login(api: &ApiClient, email: &str, password: &str) -> Result<LoginResponse, LoginErrors> {
+    let response = match api.call(&endpoints::Login { email, password }).await {
+        Err(err) if err.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => {
+            return Err(LoginErrors::InvalidCredentials);
+        }
+        response => response?,
+    };
 
-    if !response_body.success {
+    if !response.success {
         return Err(LoginErrors::Unknown("Success set to false".to_string()));
     }
 
-    match response_body.action_required {
+    match response.action_required {
         BoolOrString::Bool(true) => {
             return Err(LoginErrors::Unknown(
                 "action_required set to true".to_string(),
@@ -153,129 +116,238 @@ pub async fn login(host: &str, email: &str, password: &str) -> Result<LoginRespo
         _ => (),
     }
 
-    Ok(response_body)
+    Ok(response)
 }
 
-pub async fn remotelogin(host: &str, port: u16) -> Result<RedirectResponse, LoginErrors> {
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/134.0.0.0 Safari/537.3")
-        .build()
-        .map_err(|err| LoginErrors::Unknown(err.to_string()))?;
-
-    let endpoint = if cfg!(debug_assertions) {
-        format!("https://{}:3000/api/auth/42", host)
-    } else {
-        format!("https://{}:8443/api/auth/42", host)
+/// Logs in with `credentials` without asking anything, the 2FA code comes
+/// from the stored secret.
+pub async fn login_with_credentials(api: &ApiClient, credentials: &Credentials) -> Result<Session, LoginErrors> {
+    let response = login(api, &credentials.email, &credentials.password).await?;
+    let response = match response.action_required {
+        BoolOrString::String(_) => {
+            let totp_code = match credentials.totp_code() {
+                Some(Ok(code)) => code,
+                Some(Err(err)) => return Err(LoginErrors::Rejected(err.to_string())),
+                None => {
+                    return Err(LoginErrors::Rejected(
+                        "The account uses 2FA, a TOTP secret is needed".to_string(),
+                    ));
+                }
+            };
+            match login2fa(&api.with_token(&response.token), &totp_code).await {
+                Ok(response) => response,
+                Err(TotpErrors::Api(err)) => return Err(LoginErrors::Api(err)),
+                Err(TotpErrors::InvalidTotp) => {
+                    return Err(LoginErrors::Rejected(
+                        "The generated 2FA code was refused, check the secret and the clock".to_string(),
+                    ));
+                }
+                Err(TotpErrors::Unknown(err)) => return Err(LoginErrors::Unknown(err)),
+            }
+        }
+        _ => response,
     };
 
-    let response = client
-        .get(endpoint)
-        .query(&[("cli_port", port)])
-        .send()
-        .await
-        .map_err(|_| LoginErrors::ConnectionError)?;
-
-    if response.status().is_server_error() {
-        return Err(LoginErrors::ServerError);
-    } else if response.status().is_client_error() {
-        return Err(LoginErrors::Unknown(
-            "Received client error from redirect response".to_string(),
-        ));
+    Ok(Session::new(response.token, response.refresh_token))
+}
+
+pub async fn signup(
+    api: &ApiClient,
+    nickname: &str,
+    email: &str,
+    password: &str,
+) -> Result<LoginResponse, LoginErrors> {
+    let request = endpoints::Signup {
+        nickname,
+        email,
+        password,
+    };
+    let response = match api.call(&request).await {
+        Err(err) if err.status() == Some(reqwest::StatusCode::CONFLICT) => {
+            return Err(LoginErrors::EmailTaken);
+        }
+        response => response?,
+    };
+
+    if !response.success {
+        return Err(LoginErrors::Unknown("Success set to false".to_string()));
     }
 
-    let response_body: RedirectResponse = response
-        .json()
-        .await
-        .map_err(|_| LoginErrors::InvalidResponse)?;
+    Ok(response)
+}
 
-    Ok(response_body)
+/// Length of the random state of a login through 42.
+const CALLBACK_STATE_LENGTH: usize = 32;
+
+/// Random value a login through 42 carries to the callback, so the listener
+/// only accepts the login it started.
+pub fn callback_state() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(CALLBACK_STATE_LENGTH)
+        .map(char::from)
+        .collect()
 }
 
-pub async fn set_nickname(
-    host: &str,
-    token: &str,
-    nickname: &str,
-) -> Result<NicknameResponse, LoginErrors> {
-    let body = SetNicknameRequest { nickname };
-
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/134.0.0.0 Safari/537.3")
-        .build()
-        .map_err(|err| LoginErrors::Unknown(err.to_string()))?;
-
-    let endpoint = if cfg!(debug_assertions) {
-        format!("https://{}:3000/api/profile/set-nickname", host)
-    } else {
-        format!("https://{}:8443/api/profile/set-nickname", host)
+pub async fn remotelogin(api: &ApiClient, port: u16, state: &str) -> Result<RedirectResponse, LoginErrors> {
+    Ok(api
+        .call(&endpoints::RemoteLogin {
+            cli_port: port,
+            cli_state: state,
+        })
+        .await?)
+}
+
+pub async fn set_nickname(api: &ApiClient, nickname: &str) -> Result<NicknameResponse, LoginErrors> {
+    Ok(api.call(&endpoints::SetNickname { nickname }).await?)
+}
+
+pub async fn login2fa(api: &ApiClient, totp_code: &str) -> Result<LoginResponse, TotpErrors> {
+    let response = match api.call(&endpoints::Login2fa { totp_code }).await {
+        Err(err) if err.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => return Err(TotpErrors::InvalidTotp),
+        response => response?,
     };
 
-    let response = client
-        .post(endpoint)
-        .header("Authorization", format!("Bearer {}", token))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|_| LoginErrors::ConnectionError)?;
-
-    if response.status().is_server_error() {
-        return Err(LoginErrors::ServerError);
-    } else if response.status().is_client_error() {
-        return Err(LoginErrors::Unknown(
-            "Received client error from nickname response".to_string(),
-        ));
+    if !response.success {
+        return Err(TotpErrors::Unknown("Success set to false".to_string()));
     }
 
-    let response_body: NicknameResponse = response
-        .json()
-        .await
-        .map_err(|_| LoginErrors::InvalidResponse)?;
+    Ok(response)
+}
+
+/// Checks whether the token of `api` is still accepted by the gateway.
+pub async fn verify(api: &ApiClient) -> Result<bool, LoginErrors> {
+    match api.call(&endpoints::Verify {}).await {
+        Ok(_) => Ok(true),
+        Err(err) if err.is_unauthorized() => Ok(false),
+        // Other errors say nothing about the token, the saved session stays
+        Err(err) => Err(err.into()),
+    }
+}
 
-    Ok(response_body)
+/// Trades a refresh token for a new access token. The refresh token is spent,
+/// the response carries the next one.
+pub async fn refresh(api: &ApiClient, refresh_token: &str) -> Result<RefreshResponse, LoginErrors> {
+    match api.call(&endpoints::Refresh { refresh_token }).await {
+        Err(err) if err.is_unauthorized() => Err(LoginErrors::InvalidCredentials),
+        response => Ok(response?),
+    }
 }
 
-pub async fn login2fa(
-    host: &str,
-    auth_token: &str,
-    totp_code: &str,
-) -> Result<LoginResponse, TotpErrors> {
-    let body = TotpRequest { totp_code };
-
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/134.0.0.0 Safari/537.3")
-        .build()
-        .map_err(|err| TotpErrors::Unknown(err.to_string()))?;
-
-    let endpoint = if cfg!(debug_assertions) {
-        format!("https://{}:3000/api/auth/2fa/login", host)
-    } else {
-        format!("https://{}:8443/api/auth/2fa/login", host)
+/// Checks a saved session and refreshes it once if it was rejected, `None`
+/// when it can't be used anymore.
+pub async fn resume(api: &ApiClient, session: &Session) -> Result<Option<Session>, LoginErrors> {
+    if verify(&api.with_token(&session.token)).await? {
+        return Ok(Some(session.clone()));
+    }
+    let Some(refresh_token) = &session.refresh_token else {
+        return Ok(None);
     };
 
-    let response = client
-        .post(endpoint)
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|_| TotpErrors::ConnectionError)?;
-
-    if response.status().is_server_error() {
-        return Err(TotpErrors::ServerError);
-    } else if response.status().is_client_error() {
-        return Err(TotpErrors::InvalidTotp);
+    let response = match refresh(api, refresh_token).await {
+        Ok(response) => response,
+        Err(LoginErrors::InvalidCredentials) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    match verify(&api.with_token(&response.token)).await? {
+        true => Ok(Some(Session::new(response.token, Some(response.refresh_token)))),
+        false => Ok(None),
     }
+}
 
-    let response_body: LoginResponse = response
-        .json()
-        .await
-        .map_err(|_| TotpErrors::InvalidResponse)?;
+/// Reads the claims of a JWT without checking its signature, that is the
+/// server's job.
+fn token_claims(token: &str) -> Option<TokenClaims> {
+    let payload = token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&payload).ok()
+}
 
-    if !response_body.success {
-        return Err(TotpErrors::Unknown("Success set to false".to_string()));
+pub fn token_expiry(token: &str) -> Option<SystemTime> {
+    token_claims(token)?
+        .exp
+        .map(|exp| UNIX_EPOCH + Duration::from_secs(exp))
+}
+
+/// Whether `token` still waits for a nickname or a 2FA code, the gateway
+/// refuses those for everything else.
+pub fn is_preauth(token: &str) -> bool {
+    token_claims(token).is_none_or(|claims| claims.nickname_required || claims.totp_required)
+}
+
+/// User id of `token`, it tells accounts apart even after a nickname change.
+pub fn token_subject(token: &str) -> Option<String> {
+    token_claims(token)?.sub
+}
+
+pub fn token_nickname(token: &str) -> Option<String> {
+    token_claims(token)?.nickname
+}
+
+/// Whether `token` is a 42 login that still needs a nickname, `None` if it
+/// isn't a token at all.
+pub fn nickname_required(token: &str) -> Option<bool> {
+    token_claims(token).map(|claims| claims.nickname_required)
+}
+
+/// Account details, for accounts without 2FA this also creates the secret to
+/// enroll with.
+pub async fn account(api: &ApiClient) -> Result<AccountResponse, LoginErrors> {
+    Ok(api.call(&endpoints::Account {}).await?)
+}
+
+/// Turns 2FA on (`enable`) or off, both need a current code.
+pub async fn set_totp(api: &ApiClient, enable: bool, totp_code: &str) -> Result<(), TotpErrors> {
+    let response = match enable {
+        true => api.call(&endpoints::EnableTotp { totp_code }).await,
+        false => api.call(&endpoints::DisableTotp { totp_code }).await,
+    };
+
+    match response {
+        Err(err) if err.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => Err(TotpErrors::InvalidTotp),
+        response => response.map(|_| ()).map_err(TotpErrors::from),
     }
+}
+
+/// The gateway answers without revoking the token, so it stays valid until
+/// it expires.
+pub async fn logout(api: &ApiClient) -> Result<(), LoginErrors> {
+    api.call(&endpoints::Logout {}).await?;
+    Ok(())
+}
+
+pub async fn profile(api: &ApiClient) -> Result<ProfileResponse, LoginErrors> {
+    Ok(api.call(&endpoints::Profile {}).await?)
+}
+
+/// Changes the nickname, the server answers with a token that carries it.
+pub async fn update_nickname(api: &ApiClient, nickname: &str) -> Result<UpdateProfileResponse, LoginErrors> {
+    Ok(api.call(&endpoints::UpdateProfile { nickname }).await?)
+}
+
+/// Sets the e-mail and, if given, a new password. Local accounts only since
+/// the current password is required.
+pub async fn update_account(
+    api: &ApiClient,
+    email: &str,
+    current_password: &str,
+    new_password: Option<&str>,
+) -> Result<(), LoginErrors> {
+    let request = endpoints::UpdateAccount {
+        email,
+        current_password,
+        new_password,
+    };
+    api.call(&request).await?;
+    Ok(())
+}
 
-    Ok(response_body)
+/// Deletes the account, local accounts need their password and 2FA turned off.
+pub async fn delete_account(
+    api: &ApiClient,
+    password: Option<&str>,
+    totp_code: Option<&str>,
+) -> Result<(), LoginErrors> {
+    api.call(&endpoints::DeleteAccount { password, totp_code }).await?;
+    Ok(())
 }
@@ -0,0 +1,504 @@
+use std::{env, fmt::Display};
+
+use crossterm::terminal;
+use ratatui::{style::Color, symbols::Marker};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// `NO_COLOR` is set, everything uses the terminal's default colors.
+    NoColor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Glyphs {
+    Braille,
+    Blocks,
+    Ascii,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+}
+
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub term: Option<String>,
+    pub colorterm: Option<String>,
+    pub term_program: Option<String>,
+    pub multiplexer: Option<&'static str>,
+    pub locale: Option<String>,
+    pub color_depth: ColorDepth,
+    pub glyphs: Glyphs,
+    pub kitty_keyboard: bool,
+    pub graphics: Option<GraphicsProtocol>,
+    pub size: Option<(u16, u16)>,
+    /// A browser can be opened on this machine, not the case over SSH or in
+    /// a container without a display.
+    pub browser: bool,
+}
+
+impl Display for ColorDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TrueColor => write!(f, "24-bit truecolor"),
+            Self::Ansi256 => write!(f, "256 colors"),
+            Self::Ansi16 => write!(f, "16 colors"),
+            Self::NoColor => write!(f, "no colors (NO_COLOR)"),
+        }
+    }
+}
+
+impl Display for Glyphs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Braille => write!(f, "Unicode with Braille patterns"),
+            Self::Blocks => write!(f, "Unicode block elements only"),
+            Self::Ascii => write!(f, "ASCII only"),
+        }
+    }
+}
+
+impl Display for GraphicsProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Kitty => write!(f, "kitty graphics protocol"),
+            Self::Sixel => write!(f, "sixel"),
+        }
+    }
+}
+
+impl Glyphs {
+    /// In ASCII mode the canvas is drawn with full blocks, which `Game` swaps
+    /// for plain characters afterwards.
+    pub fn ball_marker(&self) -> Marker {
+        match self {
+            Self::Braille => Marker::Braille,
+            Self::Blocks => Marker::HalfBlock,
+            Self::Ascii => Marker::Block,
+        }
+    }
+
+    pub fn paddle_marker(&self) -> Marker {
+        match self {
+            Self::Braille | Self::Blocks => Marker::HalfBlock,
+            Self::Ascii => Marker::Block,
+        }
+    }
+}
+
+/// Width and height of a terminal cell in pixels. Terminals that leave the pixel
+/// fields of the window size at zero give `None`.
+pub fn cell_size() -> Option<(f64, f64)> {
+    let size = terminal::window_size().ok()?;
+    if size.width == 0 || size.height == 0 || size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+
+    Some((
+        size.width as f64 / size.columns as f64,
+        size.height as f64 / size.rows as f64,
+    ))
+}
+
+/// Height of a terminal cell divided by its width.
+pub fn cell_ratio() -> Option<f64> {
+    cell_size().map(|(width, height)| height / width)
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// A browser opened from an SSH session would start on the remote machine, and
+/// on Linux and BSD one needs an X11 or Wayland display.
+fn detect_browser() -> bool {
+    if var("SSH_CONNECTION").is_some() || var("SSH_TTY").is_some() {
+        return false;
+    }
+    cfg!(any(target_os = "macos", target_os = "windows")) || var("DISPLAY").is_some() || var("WAYLAND_DISPLAY").is_some()
+}
+
+impl Capabilities {
+    /// Probes the environment of the current terminal. Querying the kitty keyboard
+    /// protocol talks to the terminal, everything else only looks at env variables.
+    pub fn detect() -> Self {
+        let term = var("TERM");
+        let colorterm = var("COLORTERM");
+        let term_program = var("TERM_PROGRAM");
+        let locale = var("LC_ALL").or_else(|| var("LC_CTYPE")).or_else(|| var("LANG"));
+
+        let multiplexer = if var("TMUX").is_some() {
+            Some("tmux")
+        } else if var("STY").is_some() {
+            Some("screen")
+        } else {
+            None
+        };
+
+        Self {
+            color_depth: detect_color_depth(
+                term.as_deref(),
+                colorterm.as_deref(),
+                term_program.as_deref(),
+                var("NO_COLOR").is_some(),
+            ),
+            glyphs: detect_glyphs(term.as_deref(), locale.as_deref()),
+            graphics: detect_graphics(term.as_deref(), term_program.as_deref(), multiplexer),
+            kitty_keyboard: terminal::supports_keyboard_enhancement().unwrap_or(false),
+            size: terminal::size().ok(),
+            browser: detect_browser(),
+            term,
+            colorterm,
+            term_program,
+            multiplexer,
+            locale,
+        }
+    }
+
+    pub fn report(&self) -> String {
+        let unset = "(unset)".to_string();
+        let size = match self.size {
+            Some((width, height)) => format!("{}x{}", width, height),
+            None => "unknown".to_string(),
+        };
+        let cell_ratio = match cell_ratio() {
+            Some(ratio) => format!("{:.2}", ratio),
+            None => "unknown, assuming 1.9".to_string(),
+        };
+
+        [
+            "Terminal".to_string(),
+            format!("  TERM:            {}", self.term.as_ref().unwrap_or(&unset)),
+            format!("  COLORTERM:       {}", self.colorterm.as_ref().unwrap_or(&unset)),
+            format!("  TERM_PROGRAM:    {}", self.term_program.as_ref().unwrap_or(&unset)),
+            format!("  Multiplexer:     {}", self.multiplexer.unwrap_or("none")),
+            format!("  Locale:          {}", self.locale.as_ref().unwrap_or(&unset)),
+            format!("  Size:            {}", size),
+            format!("  Cell ratio:      {}", cell_ratio),
+            "Detected".to_string(),
+            format!("  Colors:          {}", self.color_depth),
+            format!("  Glyphs:          {}", self.glyphs),
+            format!(
+                "  Kitty keyboard:  {}",
+                if self.kitty_keyboard { "supported" } else { "not supported" }
+            ),
+            format!(
+                "  Pixel graphics:  {}",
+                self.graphics.map(|graphics| graphics.to_string()).unwrap_or("none".to_string())
+            ),
+            format!(
+                "  Web browser:     {}",
+                if self.browser { "available" } else { "none, over SSH or without a display" }
+            ),
+            "Rendering".to_string(),
+            format!("  Ball marker:     {}", self.glyphs.ball_marker()),
+            format!("  Paddle marker:   {}", self.glyphs.paddle_marker()),
+            format!(
+                "  Key release:     {}",
+                if self.kitty_keyboard { "reported by terminal" } else { "emulated with timeout" }
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+fn detect_color_depth(
+    term: Option<&str>,
+    colorterm: Option<&str>,
+    term_program: Option<&str>,
+    no_color: bool,
+) -> ColorDepth {
+    if no_color {
+        return ColorDepth::NoColor;
+    }
+
+    if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+        return ColorDepth::TrueColor;
+    }
+
+    if matches!(
+        term_program,
+        Some("iTerm.app") | Some("WezTerm") | Some("vscode") | Some("ghostty")
+    ) {
+        return ColorDepth::TrueColor;
+    }
+
+    match term {
+        Some(term) if term.ends_with("-direct") || term.contains("kitty") => ColorDepth::TrueColor,
+        Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+fn detect_graphics(
+    term: Option<&str>,
+    term_program: Option<&str>,
+    multiplexer: Option<&str>,
+) -> Option<GraphicsProtocol> {
+    // Multiplexers swallow or misplace image escape sequences
+    if multiplexer.is_some() {
+        return None;
+    }
+
+    match (term, term_program) {
+        (_, Some("WezTerm") | Some("ghostty")) => Some(GraphicsProtocol::Kitty),
+        (Some(term), _) if term.contains("kitty") || term.contains("ghostty") => {
+            Some(GraphicsProtocol::Kitty)
+        }
+        (Some(term), _) if term.starts_with("foot") || term.starts_with("mlterm") => {
+            Some(GraphicsProtocol::Sixel)
+        }
+        _ => None,
+    }
+}
+
+fn detect_glyphs(term: Option<&str>, locale: Option<&str>) -> Glyphs {
+    let utf8 = locale
+        .map(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+        .unwrap_or(false);
+
+    match (utf8, term) {
+        (false, _) => Glyphs::Ascii,
+        // The linux console font has block elements but no Braille patterns
+        (true, Some("linux")) => Glyphs::Blocks,
+        (true, _) => Glyphs::Braille,
+    }
+}
+
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => ANSI16[index as usize].1,
+        16..232 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - value as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    match distance(indexed_to_rgb(gray), rgb) < distance(indexed_to_rgb(cube), rgb) {
+        true => gray,
+        false => cube,
+    }
+}
+
+fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(*ansi, rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// RGB value of a color, `None` for the terminal default colors.
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_to_rgb(index)),
+        Color::Reset => None,
+        color => ANSI16
+            .iter()
+            .find(|(ansi, _)| *ansi == color)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+impl ColorDepth {
+    /// Maps a color onto the closest color this terminal is able to show.
+    pub fn downgrade(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::NoColor, _) => Color::Reset,
+            (Self::TrueColor, color) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_ansi256((r, g, b))),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => rgb_to_ansi16((r, g, b)),
+            (Self::Ansi16, Color::Indexed(index)) => rgb_to_ansi16(indexed_to_rgb(index)),
+            (_, color) => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::theme::Theme;
+
+    #[test]
+    fn color_depth_from_environment() {
+        let cases = [
+            (Some("xterm"), Some("truecolor"), None, false, ColorDepth::TrueColor),
+            (Some("xterm"), Some("24bit"), None, false, ColorDepth::TrueColor),
+            (Some("xterm"), None, Some("WezTerm"), false, ColorDepth::TrueColor),
+            (Some("xterm-kitty"), None, None, false, ColorDepth::TrueColor),
+            (Some("xterm-direct"), None, None, false, ColorDepth::TrueColor),
+            (Some("xterm-256color"), None, None, false, ColorDepth::Ansi256),
+            (Some("screen-256color"), Some("yes"), None, false, ColorDepth::Ansi256),
+            (Some("linux"), None, None, false, ColorDepth::Ansi16),
+            (None, None, None, false, ColorDepth::Ansi16),
+            (Some("xterm-256color"), Some("truecolor"), None, true, ColorDepth::NoColor),
+            (Some("linux"), None, None, true, ColorDepth::NoColor),
+        ];
+
+        for (term, colorterm, term_program, no_color, expected) in cases {
+            assert_eq!(
+                detect_color_depth(term, colorterm, term_program, no_color),
+                expected,
+                "TERM={:?} COLORTERM={:?} TERM_PROGRAM={:?} NO_COLOR={}",
+                term,
+                colorterm,
+                term_program,
+                no_color
+            );
+        }
+    }
+
+    #[test]
+    fn glyphs_from_term_and_locale() {
+        let cases = [
+            (Some("xterm-256color"), Some("en_US.UTF-8"), Glyphs::Braille),
+            (Some("xterm-256color"), Some("C.utf8"), Glyphs::Braille),
+            (Some("linux"), Some("en_US.UTF-8"), Glyphs::Blocks),
+            (Some("xterm-256color"), Some("C"), Glyphs::Ascii),
+            (Some("xterm-256color"), None, Glyphs::Ascii),
+        ];
+
+        for (term, locale, expected) in cases {
+            assert_eq!(detect_glyphs(term, locale), expected, "TERM={:?} locale={:?}", term, locale);
+        }
+    }
+
+    #[test]
+    fn ansi256_maps_cube_corners_and_grays() {
+        let cases = [
+            ((0, 0, 0), 16),
+            ((255, 0, 0), 196),
+            ((0, 255, 0), 46),
+            ((0, 0, 255), 21),
+            ((255, 255, 0), 226),
+            ((0, 255, 255), 51),
+            ((255, 0, 255), 201),
+            ((255, 255, 255), 231),
+            // Mid grays are closer to the gray ramp than to the cube
+            ((128, 128, 128), 244),
+        ];
+
+        for (rgb, expected) in cases {
+            assert_eq!(rgb_to_ansi256(rgb), expected, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn ansi16_maps_to_closest_named_color() {
+        let cases = [
+            ((0, 0, 0), Color::Black),
+            ((205, 0, 0), Color::Red),
+            ((255, 0, 0), Color::LightRed),
+            ((0, 0, 255), Color::Blue),
+            ((0, 255, 255), Color::LightCyan),
+            ((255, 255, 255), Color::White),
+        ];
+
+        for (rgb, expected) in cases {
+            assert_eq!(rgb_to_ansi16(rgb), expected, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn downgrade_per_color_depth() {
+        let magenta = Color::Rgb(255, 0, 255);
+        assert_eq!(ColorDepth::TrueColor.downgrade(magenta), magenta);
+        assert_eq!(ColorDepth::Ansi256.downgrade(magenta), Color::Indexed(201));
+        assert_eq!(ColorDepth::Ansi16.downgrade(magenta), Color::LightMagenta);
+        assert_eq!(ColorDepth::Ansi16.downgrade(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(ColorDepth::NoColor.downgrade(magenta), Color::Reset);
+
+        // Named colors are already part of every palette
+        assert_eq!(ColorDepth::Ansi256.downgrade(Color::Yellow), Color::Yellow);
+        assert_eq!(ColorDepth::Ansi16.downgrade(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn theme_adapts_to_capabilities() {
+        let mut capabilities = Capabilities {
+            term: None,
+            colorterm: None,
+            term_program: None,
+            multiplexer: None,
+            locale: None,
+            color_depth: ColorDepth::Ansi16,
+            glyphs: Glyphs::Ascii,
+            kitty_keyboard: false,
+            graphics: None,
+            size: None,
+            browser: false,
+        };
+
+        let theme = Theme::default().adapt(&capabilities);
+        assert_eq!(theme.paddle_a, Color::LightMagenta);
+        assert_eq!(theme.paddle_b, Color::LightCyan);
+        assert_eq!(theme.muted, Color::Gray);
+        assert!(theme.ascii_borders);
+
+        capabilities.color_depth = ColorDepth::NoColor;
+        capabilities.glyphs = Glyphs::Braille;
+        let theme = Theme::default().adapt(&capabilities);
+        assert_eq!(theme.ball, Color::Reset);
+        assert_eq!(theme.error, Color::Reset);
+        assert!(!theme.ascii_borders);
+        assert!(theme.selection(true).add_modifier.contains(ratatui::style::Modifier::REVERSED));
+    }
+}
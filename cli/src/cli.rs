@@ -0,0 +1,312 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+    api::{
+        ApiClient, ApiError,
+        endpoints::{self, Match, StatisticsResponse},
+    },
+    app::Start,
+    auth::{self, LoginErrors},
+    config::{Config, ConfigErrors, Profile},
+    credentials::{CredentialErrors, Credentials},
+    server::{Server, ServerErrors},
+    session::{Session, SessionErrors, SessionStore},
+    websocket::{
+        SocketIoClient,
+        events::{errors::EventError, request::CreateRoomRequest},
+    },
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Terminal client for ft_transcendence Pong")]
+pub struct Cli {
+    /// Server to use, a host name or a base URL like https://localhost:8443
+    #[arg(long, global = true)]
+    pub host: Option<String>,
+    /// Profile of the config file to apply
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Theme preset or custom theme of the config file
+    #[arg(long, global = true)]
+    pub theme: Option<String>,
+    /// Key preset (arrows, wasd, vim) and bindings, e.g. wasd,pause=space
+    #[arg(long, global = true)]
+    pub keymap: Option<String>,
+    /// Fixed port for the 42 login callback, e.g. to forward it over SSH
+    #[arg(long, global = true)]
+    pub callback_port: Option<u16>,
+    /// Credentials file of a bot or CI account, the FT_TRANSCENDENCE_EMAIL,
+    /// FT_TRANSCENDENCE_PASSWORD and FT_TRANSCENDENCE_TOTP_SECRET variables win
+    #[arg(long, global = true)]
+    pub credentials: Option<PathBuf>,
+    /// Print the detected terminal capabilities and exit
+    #[arg(long)]
+    pub doctor: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start a match right away, in a new room or against the AI
+    Play {
+        /// Play alone against the AI
+        #[arg(long)]
+        single: bool,
+    },
+    /// Join the room with the given id
+    Join { room_id: String },
+    /// Create a room, print its id and wait in the lobby
+    Host,
+    /// Print your game statistics
+    Stats {
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print your most recent matches
+    History {
+        /// Number of matches to print
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Log in with the credentials of the environment or file and save the session
+    Login,
+    /// Log out and delete the saved session of the host
+    Logout,
+}
+
+#[derive(Debug)]
+pub enum CommandErrors {
+    Server(ServerErrors),
+    NoHost,
+    NotLoggedIn(Server),
+    NoCredentials,
+    Credentials(CredentialErrors),
+    Login(LoginErrors),
+    Api(ApiError),
+    Session(SessionErrors),
+    Room(EventError),
+    Json(serde_json::Error),
+}
+
+impl Display for CommandErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Server(err) => write!(f, "{}", err),
+            Self::NoHost => write!(f, "No host given and no saved session, use --host"),
+            Self::NotLoggedIn(host) => write!(f, "Not logged in to {}, start the client without a command to log in", host),
+            Self::NoCredentials => write!(
+                f,
+                "No credentials, set FT_TRANSCENDENCE_EMAIL and FT_TRANSCENDENCE_PASSWORD or use --credentials"
+            ),
+            Self::Credentials(err) => write!(f, "{}", err),
+            Self::Login(err) => write!(f, "{}", err),
+            Self::Api(err) => write!(f, "{}", err),
+            Self::Session(err) => write!(f, "{}", err),
+            Self::Room(err) => write!(f, "Unable to create a room: {}", err),
+            Self::Json(err) => write!(f, "Unable to encode the output: {}", err),
+        }
+    }
+}
+
+impl Error for CommandErrors {}
+
+impl Cli {
+    /// Applies the profile and the options to `config`, the options win.
+    pub fn configure(&self, config: &mut Config) -> Result<(), ConfigErrors> {
+        if let Some(profile) = &self.profile {
+            config.apply_profile(profile)?;
+        }
+        config.set_overrides(Profile {
+            host: self.host.clone(),
+            theme: self.theme.clone(),
+            keymap: self.keymap.clone(),
+            callback_port: self.callback_port,
+            ..Profile::default()
+        })
+    }
+}
+
+/// Runs `command`. Commands that continue in the TUI return where it starts,
+/// all others are done once this returns. With credentials nothing asks for
+/// a login, a missing or expired session is replaced by a new login.
+pub async fn run(command: Command, config: &Config, credentials: Option<&Path>) -> Result<Option<Start>, CommandErrors> {
+    let credentials = Credentials::load(credentials).map_err(CommandErrors::Credentials)?;
+    let start = match &command {
+        Command::Play { single } => Some(Start::Play { single: *single }),
+        Command::Join { room_id } => Some(Start::Join(room_id.to_ascii_uppercase())),
+        _ => None,
+    };
+    if start.is_some() && credentials.is_none() {
+        return Ok(start);
+    }
+
+    let mut sessions = match config.remember_session() {
+        true => SessionStore::load().map_err(CommandErrors::Session)?,
+        false => SessionStore::default(),
+    };
+    let host = match (config.host(), sessions.last_host.as_deref()) {
+        (Some(host), _) | (None, Some(host)) => Server::parse(host).map_err(CommandErrors::Server)?,
+        (None, None) => return Err(CommandErrors::NoHost),
+    };
+    let api = ApiClient::new().map_err(CommandErrors::Api)?.for_host(&host);
+
+    if let Command::Logout = command {
+        match sessions.remove(host.as_str()) {
+            Some(session) => {
+                sessions.save().map_err(CommandErrors::Session)?;
+                auth::logout(&api.with_token(&session.token))
+                    .await
+                    .map_err(CommandErrors::Login)?;
+                println!("Logged out of {}", host);
+            }
+            None => println!("No session saved for {}", host),
+        }
+        return Ok(None);
+    }
+
+    if let Command::Login = command {
+        let credentials = credentials.ok_or(CommandErrors::NoCredentials)?;
+        login(&api, &host, &credentials, &mut sessions, config).await?;
+        match config.remember_session() {
+            true => println!("Logged in to {} as {}", host, credentials.email),
+            false => println!("Logged in to {} as {}, the session is not kept", host, credentials.email),
+        }
+        return Ok(None);
+    }
+
+    let session = match resume(&api, &host, &mut sessions).await {
+        Err(CommandErrors::NotLoggedIn(_)) if let Some(credentials) = &credentials => {
+            login(&api, &host, credentials, &mut sessions, config).await?
+        }
+        session => session?,
+    };
+    if let Some(start) = start {
+        return Ok(Some(Start::LoggedIn {
+            host,
+            session,
+            start: Box::new(start),
+        }));
+    }
+    let api = api.with_token(&session.token);
+
+    match command {
+        Command::Host => {
+            let endpoint = host.socket("", &session.token);
+            let mut socket = SocketIoClient::new(&endpoint, &session.token)
+                .await
+                .map_err(|err| CommandErrors::Room(err.into()))?;
+            let room_id = socket
+                .create_room(CreateRoomRequest::multiplayer())
+                .await
+                .map_err(CommandErrors::Room)?;
+            println!("{}", room_id);
+            Ok(Some(Start::Lobby {
+                host,
+                session,
+                socket: Box::new(socket),
+                room_id,
+            }))
+        }
+        Command::Stats { json } => {
+            let stats = api.call(&endpoints::MyStatistics {}).await.map_err(CommandErrors::Api)?;
+            match json {
+                true => print_json(&stats)?,
+                false => print_statistics(&stats),
+            }
+            Ok(None)
+        }
+        Command::History { limit, json } => {
+            let history = api.call(&endpoints::MyMatches { limit }).await.map_err(CommandErrors::Api)?;
+            match json {
+                true => print_json(&history)?,
+                false => print_matches(&history.matches),
+            }
+            Ok(None)
+        }
+        Command::Play { .. } | Command::Join { .. } | Command::Login | Command::Logout => Ok(None),
+    }
+}
+
+/// Logs in with `credentials` and saves the session, unless sessions aren't
+/// remembered.
+async fn login(
+    api: &ApiClient,
+    host: &Server,
+    credentials: &Credentials,
+    sessions: &mut SessionStore,
+    config: &Config,
+) -> Result<Session, CommandErrors> {
+    let session = auth::login_with_credentials(api, credentials)
+        .await
+        .map_err(CommandErrors::Login)?;
+    if config.remember_session() {
+        sessions.insert(host.as_str(), &session.token, session.refresh_token.as_deref());
+        sessions.save().map_err(CommandErrors::Session)?;
+    }
+    Ok(session)
+}
+
+/// The saved session of `host`, refreshed and written back if needed.
+async fn resume(api: &ApiClient, host: &Server, sessions: &mut SessionStore) -> Result<Session, CommandErrors> {
+    let saved = sessions
+        .get(host.as_str())
+        .cloned()
+        .ok_or(CommandErrors::NotLoggedIn(host.clone()))?;
+
+    match auth::resume(api, &saved).await.map_err(CommandErrors::Login)? {
+        Some(session) if session.token == saved.token => Ok(session),
+        Some(session) => {
+            sessions.insert(host.as_str(), &session.token, session.refresh_token.as_deref());
+            sessions.save().map_err(CommandErrors::Session)?;
+            Ok(session)
+        }
+        None => Err(CommandErrors::NotLoggedIn(host.clone())),
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), CommandErrors> {
+    let json = serde_json::to_string_pretty(value).map_err(CommandErrors::Json)?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn print_statistics(stats: &StatisticsResponse) {
+    println!("Games played   {}", stats.games_played);
+    println!("Won            {}", stats.games_won);
+    println!("Lost           {}", stats.games_lost);
+    println!("Win rate       {}%", stats.win_rate);
+    println!("Average score  {}", stats.avg_score);
+    println!("Total score    {}", stats.total_score);
+    if let Some(date) = &stats.last_game_date {
+        println!("Last game      {}", date);
+    }
+}
+
+fn print_matches(matches: &[Match]) {
+    if matches.is_empty() {
+        println!("No matches played yet");
+    }
+    for game in matches {
+        let opponent = game.opponent_nickname.as_deref().unwrap_or("AI");
+        println!(
+            "{}  {:<4}  {:>2}:{:<2}  {:<20}  {}",
+            game.played_at,
+            game.result,
+            game.my_score,
+            game.opponent_score.unwrap_or_default(),
+            opponent,
+            game.game_type,
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fmt::Display,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use toml_edit::DocumentMut;
+
+use crate::{
+    capabilities::GraphicsProtocol,
+    game::keymap::Keymap,
+    server::Server,
+    ui::theme::{self, Theme, ThemeConfig},
+};
+
+pub const DEFAULT_FPS: u32 = 60;
+pub const DEFAULT_MOVEMENT_TIMEOUT_MS: u64 = 30;
+const FPS_RANGE: std::ops::RangeInclusive<u32> = 10..=240;
+const MOVEMENT_TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 10..=1000;
+pub const ENV_PREFIX: &str = "FT_TRANSCENDENCE_";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub host: Option<String>,
+    pub theme: Option<String>,
+    pub keymap: Option<String>,
+    pub fps: Option<u32>,
+    pub movement_timeout_ms: Option<u64>,
+    pub callback_port: Option<u16>,
+    #[serde(default)]
+    pub graphics: GraphicsMode,
+    #[serde(default)]
+    pub hud: HudConfig,
+    pub start_delay_ms: Option<u64>,
+    #[serde(default)]
+    pub start_notification: StartNotification,
+    pub remember_session: Option<bool>,
+    #[serde(default)]
+    pub themes: HashMap<String, ThemeConfig>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// Set from the environment and the command line, wins over everything.
+    #[serde(skip)]
+    overrides: Profile,
+    #[serde(skip)]
+    profile: Option<String>,
+    #[serde(skip)]
+    selected_host: Option<Server>,
+}
+
+/// Named set of settings replacing the top-level ones it sets. Picked with
+/// `--profile`, or by its `host` once that host is selected.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub host: Option<String>,
+    pub theme: Option<String>,
+    pub keymap: Option<String>,
+    pub fps: Option<u32>,
+    pub movement_timeout_ms: Option<u64>,
+    /// Port of the listener for the 42 login, a random one if unset.
+    pub callback_port: Option<u16>,
+}
+
+/// Top-level values edited by the settings page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub values: Profile,
+    pub graphics: GraphicsMode,
+    pub start_notification: StartNotification,
+    pub remember_session: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphicsMode {
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    Off,
+}
+
+/// How the terminal is told that a match has begun.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartNotification {
+    #[default]
+    Bell,
+    Osc,
+    Off,
+}
+
+impl StartNotification {
+    pub const ALL: [Self; 3] = [Self::Bell, Self::Osc, Self::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bell => "bell",
+            Self::Osc => "osc",
+            Self::Off => "off",
+        }
+    }
+
+    pub fn sequence(&self) -> &'static [u8] {
+        match self {
+            Self::Bell => b"\x07",
+            // OSC 777 for VTE based terminals and OSC 9 for iTerm2, WezTerm and others
+            Self::Osc => b"\x1b]777;notify;Pong;The match has started\x1b\\\x1b]9;The match has started\x07",
+            Self::Off => b"",
+        }
+    }
+}
+
+/// Elements of the in-game HUD, all shown unless turned off.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HudConfig {
+    pub clock: bool,
+    pub rally: bool,
+    pub longest_rally: bool,
+    pub ball_speed: bool,
+    pub paused_by: bool,
+}
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        Self {
+            clock: true,
+            rally: true,
+            longest_rally: true,
+            ball_speed: true,
+            paused_by: true,
+        }
+    }
+}
+
+impl GraphicsMode {
+    pub const ALL: [Self; 4] = [Self::Auto, Self::Kitty, Self::Sixel, Self::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Kitty => "kitty",
+            Self::Sixel => "sixel",
+            Self::Off => "off",
+        }
+    }
+
+    pub fn select(self, detected: Option<GraphicsProtocol>) -> Option<GraphicsProtocol> {
+        match self {
+            Self::Auto => detected,
+            Self::Kitty => Some(GraphicsProtocol::Kitty),
+            Self::Sixel => Some(GraphicsProtocol::Sixel),
+            Self::Off => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigErrors {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Write(PathBuf, io::Error),
+    Edit(PathBuf, toml_edit::TomlError),
+    NoConfigDir,
+    UnknownTheme(String),
+    UnknownProfile(String),
+    InvalidKeymap(String),
+    /// `key` of `source` (the file, the environment or the command line) has
+    /// an unusable value.
+    Invalid {
+        source: String,
+        key: String,
+        message: String,
+    },
+}
+
+impl Display for ConfigErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Unable to read {}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "Invalid config {}: {}", path.display(), err),
+            Self::Write(path, err) => write!(f, "Unable to write {}: {}", path.display(), err),
+            Self::Edit(path, err) => write!(f, "Unable to update {}: {}", path.display(), err),
+            Self::NoConfigDir => write!(f, "No config directory found for this system"),
+            Self::UnknownTheme(name) => write!(
+                f,
+                "Unknown theme \"{}\", expected one of the [themes] tables or {}",
+                name,
+                theme::PRESETS.join(", ")
+            ),
+            Self::UnknownProfile(name) => write!(f, "Unknown profile \"{}\", no [profiles.{}] table in the config", name, name),
+            Self::InvalidKeymap(err) => write!(f, "Invalid keymap: {}", err),
+            Self::Invalid { source, key, message } => write!(f, "{}: invalid `{}`: {}", source, key, message),
+        }
+    }
+}
+
+impl Error for ConfigErrors {}
+
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ft-transcendence"))
+}
+
+/// Writes `content` to a temporary file next to `path` and renames it over
+/// `path`, so a crash or a full disk never leaves a half-written file.
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    match written.and_then(|_| fs::rename(&tmp, path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            fs::remove_file(&tmp).ok();
+            Err(err)
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self, ConfigErrors> {
+        let mut config = Self::read()?;
+        config.validate()?;
+
+        let overrides = Profile::from_env()?;
+        config.check("environment", &overrides, |key| format!("{}{}", ENV_PREFIX, key.to_uppercase()))?;
+        config.overrides = overrides;
+        Ok(config)
+    }
+
+    fn read() -> Result<Self, ConfigErrors> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(ConfigErrors::Io(path, err)),
+        };
+
+        toml::from_str(&content).map_err(|err| ConfigErrors::Parse(path, err))
+    }
+
+    /// Checks every value of the file, so a typo in an unused profile is
+    /// reported as well.
+    fn validate(&self) -> Result<(), ConfigErrors> {
+        let source = Self::source();
+
+        for (name, custom) in &self.themes {
+            if let Some(base) = &custom.base
+                && Theme::preset(base).is_none()
+            {
+                return Err(ConfigErrors::Invalid {
+                    source,
+                    key: format!("themes.{}.base", name),
+                    message: format!("Unknown preset \"{}\", expected one of {}", base, theme::PRESETS.join(", ")),
+                });
+            }
+        }
+
+        self.check(&source, &self.top_level(), |key| key.to_string())?;
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        names.sort();
+        for name in names {
+            self.check(&source, &self.profiles[name], |key| format!("profiles.{}.{}", name, key))?;
+        }
+        Ok(())
+    }
+
+    fn source() -> String {
+        Self::path()
+            .map(|path| path.display().to_string())
+            .unwrap_or("config".to_string())
+    }
+
+    /// Checks the values `values` sets, `key` names them for the error.
+    pub fn check(&self, source: &str, values: &Profile, key: impl Fn(&str) -> String) -> Result<(), ConfigErrors> {
+        let invalid = |name: &str, message: String| ConfigErrors::Invalid {
+            source: source.to_string(),
+            key: key(name),
+            message,
+        };
+
+        if let Some(host) = &values.host {
+            Server::parse(host).map_err(|err| invalid("host", err.to_string()))?;
+        }
+        if let Some(name) = &values.theme {
+            self.resolve_theme(name).map_err(|err| invalid("theme", err.to_string()))?;
+        }
+        if let Some(keymap) = &values.keymap {
+            keymap.parse::<Keymap>().map_err(|err| invalid("keymap", err))?;
+        }
+        if let Some(fps) = values.fps
+            && !FPS_RANGE.contains(&fps)
+        {
+            return Err(invalid(
+                "fps",
+                format!("{} is out of range, expected {} to {}", fps, FPS_RANGE.start(), FPS_RANGE.end()),
+            ));
+        }
+        if let Some(timeout) = values.movement_timeout_ms
+            && !MOVEMENT_TIMEOUT_RANGE.contains(&timeout)
+        {
+            return Err(invalid(
+                "movement_timeout_ms",
+                format!(
+                    "{} is out of range, expected {} to {}",
+                    timeout,
+                    MOVEMENT_TIMEOUT_RANGE.start(),
+                    MOVEMENT_TIMEOUT_RANGE.end()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn top_level(&self) -> Profile {
+        Profile {
+            host: self.host.clone(),
+            theme: self.theme.clone(),
+            keymap: self.keymap.clone(),
+            fps: self.fps,
+            movement_timeout_ms: self.movement_timeout_ms,
+            callback_port: self.callback_port,
+        }
+    }
+
+    /// Applies the options of the command line, they win over the environment.
+    pub fn set_overrides(&mut self, values: Profile) -> Result<(), ConfigErrors> {
+        self.check("command line", &values, |key| format!("--{}", key.replace('_', "-")))?;
+        let overrides = &mut self.overrides;
+        overrides.host = values.host.or(overrides.host.take());
+        overrides.theme = values.theme.or(overrides.theme.take());
+        overrides.keymap = values.keymap.or(overrides.keymap.take());
+        overrides.fps = values.fps.or(overrides.fps);
+        overrides.movement_timeout_ms = values.movement_timeout_ms.or(overrides.movement_timeout_ms);
+        overrides.callback_port = values.callback_port.or(overrides.callback_port);
+        Ok(())
+    }
+
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), ConfigErrors> {
+        if !self.profiles.contains_key(name) {
+            return Err(ConfigErrors::UnknownProfile(name.to_string()));
+        }
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Makes the profile whose `host` is `host` apply, if there is one.
+    pub fn select_host(&mut self, host: &Server) {
+        self.selected_host = Some(host.clone());
+    }
+
+    /// Layers from the strongest: environment and command line, the profile of
+    /// `--profile`, the profile of the selected host and the top-level values.
+    fn layers(&self) -> impl Iterator<Item = &Profile> {
+        let named = self.profile.as_ref().and_then(|name| self.profiles.get(name));
+        let by_host = self.selected_host.as_ref().and_then(|selected| {
+            self.profiles.values().find(|profile| {
+                profile
+                    .host
+                    .as_deref()
+                    .and_then(|host| Server::parse(host).ok())
+                    .is_some_and(|host| host == *selected)
+            })
+        });
+        [Some(&self.overrides), named, by_host].into_iter().flatten()
+    }
+
+    /// Host to preselect, a host profile can't pick its own host.
+    pub fn host(&self) -> Option<&str> {
+        let named = self.profile.as_ref().and_then(|name| self.profiles.get(name));
+        [self.overrides.host.as_deref(), named.and_then(|profile| profile.host.as_deref())]
+            .into_iter()
+            .flatten()
+            .next()
+            .or(self.host.as_deref())
+    }
+
+    pub fn keymap(&self) -> Result<Keymap, ConfigErrors> {
+        let keymap = self
+            .layers()
+            .find_map(|layer| layer.keymap.as_deref())
+            .or(self.keymap.as_deref());
+        match keymap {
+            Some(keymap) => keymap.parse().map_err(ConfigErrors::InvalidKeymap),
+            None => Ok(Keymap::default()),
+        }
+    }
+
+    pub fn theme_name(&self) -> &str {
+        self.layers()
+            .find_map(|layer| layer.theme.as_deref())
+            .or(self.theme.as_deref())
+            .unwrap_or("default")
+    }
+
+    /// Time between two frames of the UI.
+    pub fn tick(&self) -> Duration {
+        let fps = self
+            .layers()
+            .find_map(|layer| layer.fps)
+            .or(self.fps)
+            .unwrap_or(DEFAULT_FPS);
+        Duration::from_micros(1_000_000 / fps as u64)
+    }
+
+    /// Time without a key event after which a held paddle stops.
+    pub fn movement_timeout(&self) -> Duration {
+        let timeout = self
+            .layers()
+            .find_map(|layer| layer.movement_timeout_ms)
+            .or(self.movement_timeout_ms)
+            .unwrap_or(DEFAULT_MOVEMENT_TIMEOUT_MS);
+        Duration::from_millis(timeout)
+    }
+
+    /// Port for the callback of the 42 login, 0 lets the system pick one.
+    pub fn callback_port(&self) -> u16 {
+        self.layers()
+            .find_map(|layer| layer.callback_port)
+            .or(self.callback_port)
+            .unwrap_or_default()
+    }
+
+    pub fn settings(&self) -> Settings {
+        Settings {
+            values: self.top_level(),
+            graphics: self.graphics,
+            start_notification: self.start_notification,
+            remember_session: self.remember_session(),
+        }
+    }
+
+    /// Writes `settings` to the config file, keeping its comments and the
+    /// values the settings page doesn't edit.
+    pub fn save(&mut self, settings: Settings) -> Result<(), ConfigErrors> {
+        let path = Self::path().ok_or(ConfigErrors::NoConfigDir)?;
+        self.check(&path.display().to_string(), &settings.values, |key| key.to_string())?;
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(ConfigErrors::Io(path, err)),
+        };
+        let mut document: DocumentMut = content.parse().map_err(|err| ConfigErrors::Edit(path.clone(), err))?;
+
+        let values = &settings.values;
+        set_value(&mut document, "host", values.host.clone());
+        set_value(&mut document, "theme", values.theme.clone());
+        set_value(&mut document, "keymap", values.keymap.clone());
+        set_value(&mut document, "fps", values.fps.map(i64::from));
+        set_value(&mut document, "movement_timeout_ms", values.movement_timeout_ms.map(|ms| ms as i64));
+        set_value(&mut document, "graphics", Some(settings.graphics.name()));
+        set_value(&mut document, "start_notification", Some(settings.start_notification.name()));
+        set_value(&mut document, "remember_session", Some(settings.remember_session));
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| ConfigErrors::Write(path.clone(), err))?;
+        }
+        write_atomic(&path, &document.to_string()).map_err(|err| ConfigErrors::Write(path.clone(), err))?;
+
+        self.host = settings.values.host;
+        self.theme = settings.values.theme;
+        self.keymap = settings.values.keymap;
+        self.fps = settings.values.fps;
+        self.movement_timeout_ms = settings.values.movement_timeout_ms;
+        self.graphics = settings.graphics;
+        self.start_notification = settings.start_notification;
+        self.remember_session = Some(settings.remember_session);
+        Ok(())
+    }
+
+    pub fn remember_session(&self) -> bool {
+        self.remember_session.unwrap_or(true)
+    }
+
+    pub fn theme(&self) -> Result<Theme, ConfigErrors> {
+        self.resolve_theme(self.theme_name())
+    }
+
+    /// Presets followed by the custom themes of the config file.
+    pub fn theme_names(&self) -> Vec<String> {
+        let mut custom: Vec<String> = self
+            .themes
+            .keys()
+            .filter(|name| !theme::PRESETS.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        custom.sort();
+
+        theme::PRESETS.iter().map(|name| name.to_string()).chain(custom).collect()
+    }
+
+    pub fn resolve_theme(&self, name: &str) -> Result<Theme, ConfigErrors> {
+        if let Some(custom) = self.themes.get(name) {
+            let base = custom.base.as_deref().unwrap_or("default");
+            let base = Theme::preset(base).ok_or(ConfigErrors::UnknownTheme(base.to_string()))?;
+            return Ok(custom.apply(base));
+        }
+
+        Theme::preset(name).ok_or(ConfigErrors::UnknownTheme(name.to_string()))
+    }
+}
+
+/// Sets the top-level `key`, or removes it for `None`.
+fn set_value<V: Into<toml_edit::Value>>(document: &mut DocumentMut, key: &str, value: Option<V>) {
+    match value {
+        Some(value) => document[key] = toml_edit::value(value),
+        None => {
+            document.remove(key);
+        }
+    }
+}
+
+impl Profile {
+    /// Values of the `FT_TRANSCENDENCE_*` environment variables.
+    fn from_env() -> Result<Self, ConfigErrors> {
+        let var = |name: &str| env::var(format!("{}{}", ENV_PREFIX, name)).ok().filter(|value| !value.is_empty());
+        let number = |name: &str| -> Result<Option<u64>, ConfigErrors> {
+            var(name)
+                .map(|value| {
+                    value.trim().parse().map_err(|_| ConfigErrors::Invalid {
+                        source: "environment".to_string(),
+                        key: format!("{}{}", ENV_PREFIX, name),
+                        message: format!("\"{}\" is not a number", value),
+                    })
+                })
+                .transpose()
+        };
+
+        let values = Self {
+            host: var("HOST"),
+            theme: var("THEME"),
+            keymap: var("KEYMAP"),
+            fps: number("FPS")?.map(|fps| fps.min(u32::MAX as u64) as u32),
+            movement_timeout_ms: number("MOVEMENT_TIMEOUT_MS")?,
+            callback_port: number("CALLBACK_PORT")?
+                .map(|port| {
+                    u16::try_from(port).map_err(|_| ConfigErrors::Invalid {
+                        source: "environment".to_string(),
+                        key: format!("{}CALLBACK_PORT", ENV_PREFIX),
+                        message: format!("{} is not a port", port),
+                    })
+                })
+                .transpose()?,
+        };
+        Ok(values)
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    env,
+    error::Error,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha1::Sha1;
+
+use crate::config::{ENV_PREFIX, config_dir};
+
+/// RFC 6238 parameters of the server: SHA-1, 6 digits and 30 second steps.
+const TOTP_STEP: u64 = 30;
+const TOTP_DIGITS: u32 = 6;
+
+/// Login of an account that runs without a user, like a bot or a CI job.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Credentials {
+    pub email: String,
+    pub password: String,
+    /// Base32 secret of the 2FA setup, the codes are generated from it.
+    #[serde(default)]
+    pub totp_secret: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum CredentialErrors {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Incomplete(&'static str),
+    InvalidSecret,
+}
+
+impl Display for CredentialErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Unable to read {}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "Invalid credentials file {}: {}", path.display(), err),
+            Self::Incomplete(missing) => write!(f, "{}{} is not set", ENV_PREFIX, missing),
+            Self::InvalidSecret => write!(f, "The 2FA secret is not valid base32"),
+        }
+    }
+}
+
+impl Error for CredentialErrors {}
+
+impl Credentials {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("credentials.toml"))
+    }
+
+    /// Credentials of the environment, else of the file at `path` or the
+    /// default one. `None` when there are none at all.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>, CredentialErrors> {
+        if let Some(credentials) = Self::from_env()? {
+            return Ok(Some(credentials));
+        }
+        match path {
+            Some(path) => Self::from_file(path).map(Some),
+            None => match Self::path() {
+                Some(path) if path.exists() => Self::from_file(&path).map(Some),
+                _ => Ok(None),
+            },
+        }
+    }
+
+    /// `FT_TRANSCENDENCE_EMAIL` and `FT_TRANSCENDENCE_PASSWORD`, with an
+    /// optional `FT_TRANSCENDENCE_TOTP_SECRET`.
+    pub fn from_env() -> Result<Option<Self>, CredentialErrors> {
+        let var = |name: &str| env::var(format!("{}{}", ENV_PREFIX, name)).ok().filter(|value| !value.is_empty());
+
+        let credentials = match (var("EMAIL"), var("PASSWORD")) {
+            (None, None) => return Ok(None),
+            (Some(_), None) => return Err(CredentialErrors::Incomplete("PASSWORD")),
+            (None, Some(_)) => return Err(CredentialErrors::Incomplete("EMAIL")),
+            (Some(email), Some(password)) => Self {
+                email,
+                password,
+                totp_secret: var("TOTP_SECRET"),
+            },
+        };
+        credentials.check().map(|()| Some(credentials))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, CredentialErrors> {
+        let content = fs::read_to_string(path).map_err(|err| CredentialErrors::Io(path.to_path_buf(), err))?;
+        let credentials: Self =
+            toml::from_str(&content).map_err(|err| CredentialErrors::Parse(path.to_path_buf(), err))?;
+        credentials.check().map(|()| credentials)
+    }
+
+    /// A broken secret fails here instead of as a wrong code on the server.
+    fn check(&self) -> Result<(), CredentialErrors> {
+        match &self.totp_secret {
+            Some(secret) => decode_secret(secret).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Current 2FA code, `None` without a secret.
+    pub fn totp_code(&self) -> Option<Result<String, CredentialErrors>> {
+        self.totp_secret
+            .as_deref()
+            .map(|secret| totp_code(secret, SystemTime::now()))
+    }
+}
+
+/// Decodes a base32 secret the way authenticator apps accept it: any case,
+/// with spaces and with or without padding.
+fn decode_secret(secret: &str) -> Result<Vec<u8>, CredentialErrors> {
+    let secret: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=' && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    match base32::decode(base32::Alphabet::Rfc4648 { padding: false }, &secret) {
+        Some(key) if !key.is_empty() => Ok(key),
+        _ => Err(CredentialErrors::InvalidSecret),
+    }
+}
+
+/// RFC 6238 code of `secret` at `time`.
+pub fn totp_code(secret: &str, time: SystemTime) -> Result<String, CredentialErrors> {
+    let key = decode_secret(secret)?;
+    let counter = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / TOTP_STEP)
+        .unwrap_or_default();
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).map_err(|_| CredentialErrors::InvalidSecret)?;
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation of RFC 4226
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // "12345678901234567890", the SHA-1 key of the RFC 6238 test vectors
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn totp_matches_rfc_6238_vectors() {
+        for (time, code) in [(59, "287082"), (1111111109, "081804"), (20000000000, "353130")] {
+            assert_eq!(totp_code(RFC_SECRET, at(time)).unwrap(), code, "T = {}", time);
+        }
+    }
+
+    #[test]
+    fn secret_accepts_lowercase_spaces_and_padding() {
+        let expected = decode_secret(RFC_SECRET).unwrap();
+        assert_eq!(expected, b"12345678901234567890");
+
+        for secret in [
+            "gezdgnbvgy3tqojqgezdgnbvgy3tqojq",
+            "GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ",
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ======",
+        ] {
+            assert_eq!(decode_secret(secret).unwrap(), expected, "{}", secret);
+        }
+    }
+
+    #[test]
+    fn secret_rejects_invalid_input() {
+        for secret in ["", "====", "NOT*BASE32"] {
+            assert!(matches!(decode_secret(secret), Err(CredentialErrors::InvalidSecret)), "{}", secret);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Pause,
+    Hud,
+}
+
+pub const ACTIONS: [Action; 4] = [Action::Up, Action::Down, Action::Pause, Action::Hud];
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Up => "Move up",
+            Self::Down => "Move down",
+            Self::Pause => "Pause",
+            Self::Hud => "Toggle HUD",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keymap {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub pause: KeyCode,
+    pub hud: KeyCode,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            pause: KeyCode::Char('p'),
+            hud: KeyCode::Char('h'),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn get(&self, action: Action) -> KeyCode {
+        match action {
+            Action::Up => self.up,
+            Action::Down => self.down,
+            Action::Pause => self.pause,
+            Action::Hud => self.hud,
+        }
+    }
+
+    /// Binds `key` to `action`. An action that had `key` before gets the old key
+    /// of `action`, so no two actions share a key.
+    pub fn set(&mut self, action: Action, key: KeyCode) {
+        let previous = self.get(action);
+        if let Some(other) = ACTIONS.iter().find(|other| self.get(**other) == key) {
+            *self.slot(*other) = previous;
+        }
+        *self.slot(action) = key;
+    }
+
+    fn slot(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::Up => &mut self.up,
+            Action::Down => &mut self.down,
+            Action::Pause => &mut self.pause,
+            Action::Hud => &mut self.hud,
+        }
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        ACTIONS.into_iter().find(|action| self.get(*action) == key)
+    }
+}
+
+impl FromStr for Keymap {
+    type Err = String;
+
+    /// A preset (`arrows`, `wasd`, `vim`) followed by optional bindings, e.g.
+    /// `wasd,pause=space` or `up=i,down=k`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut keymap = Self::default();
+
+        for (index, part) in value.split(',').map(str::trim).enumerate() {
+            match (index, part.split_once('=')) {
+                (0, None) => {
+                    keymap = match part {
+                        "arrows" => Self::default(),
+                        "wasd" => Self {
+                            up: KeyCode::Char('w'),
+                            down: KeyCode::Char('s'),
+                            ..Self::default()
+                        },
+                        "vim" => Self {
+                            up: KeyCode::Char('k'),
+                            down: KeyCode::Char('j'),
+                            ..Self::default()
+                        },
+                        _ => return Err(format!("Unknown keymap \"{}\", expected arrows, wasd or vim", part)),
+                    }
+                }
+                (_, Some((action, key))) => {
+                    let action = match action.trim() {
+                        "up" => Action::Up,
+                        "down" => Action::Down,
+                        "pause" => Action::Pause,
+                        "hud" => Action::Hud,
+                        other => return Err(format!("Unknown action \"{}\", expected up, down, pause or hud", other)),
+                    };
+                    keymap.set(action, parse_key(key.trim())?);
+                }
+                (_, None) => return Err(format!("Expected action=key, got \"{}\"", part)),
+            }
+        }
+
+        Ok(keymap)
+    }
+}
+
+fn parse_key(key: &str) -> Result<KeyCode, String> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => return Ok(KeyCode::Char(c.to_ascii_lowercase())),
+        (None, _) => return Err("Missing key".to_string()),
+        (_, _) => (),
+    }
+
+    match key.to_ascii_lowercase().as_str() {
+        "up" => Ok(KeyCode::Up),
+        "down" => Ok(KeyCode::Down),
+        "left" => Ok(KeyCode::Left),
+        "right" => Ok(KeyCode::Right),
+        "space" => Ok(KeyCode::Char(' ')),
+        "enter" => Ok(KeyCode::Enter),
+        "tab" => Ok(KeyCode::Tab),
+        _ => Err(format!("Unknown key \"{}\"", key)),
+    }
+}
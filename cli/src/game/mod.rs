@@ -1 +1,3 @@
+pub mod keymap;
 pub mod movement;
+pub mod stats;
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::websocket::events::request::PaddleMoveDirection;
 
@@ -7,14 +7,17 @@ pub struct Movement {
     direction: PaddleMoveDirection,
     first_keystroke: bool,
     since: Option<Instant>,
+    timeout: Duration,
 }
 
 impl Movement {
-    pub fn new() -> Self {
+    /// `timeout` is the time without a key event after which the movement stops.
+    pub fn new(timeout: Duration) -> Self {
         Self {
             direction: PaddleMoveDirection::None,
             first_keystroke: false,
             since: None,
+            timeout,
         }
     }
 
@@ -33,8 +36,8 @@ impl Movement {
 
     pub fn movement_stopped(&mut self) -> bool {
         if self.direction != PaddleMoveDirection::None {
-            let duration = self.since.unwrap().elapsed().as_millis();
-            if duration > 30 {
+            let duration = self.since.unwrap().elapsed();
+            if duration > self.timeout {
                 self.update(&PaddleMoveDirection::None);
                 return true;
             }
@@ -0,0 +1,339 @@
+use std::time::{Duration, Instant};
+
+use crate::websocket::events::websocketevents::GameStateEvent;
+
+// Anything further than this between two states is the ball being reset after a point
+const MAX_BALL_STEP: f64 = 200.0;
+const SPEED_SMOOTHING: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PausedBy {
+    Me,
+    Opponent,
+}
+
+#[derive(Debug, Clone)]
+pub struct Point {
+    pub owner_scored: bool,
+    pub at: Duration,
+    pub rally: usize,
+}
+
+/// Match statistics derived from the `game_state` stream.
+#[derive(Debug, Clone)]
+pub struct MatchStats {
+    started: bool,
+    elapsed: Duration,
+    running_since: Option<Instant>,
+    pause_requested: bool,
+    paused_by: Option<PausedBy>,
+    last_ball: Option<(f64, f64, Instant)>,
+    direction_x: f64,
+    score: (usize, usize),
+    rally: usize,
+    longest_rally: usize,
+    ball_speed: Option<f64>,
+    speed_sum: f64,
+    speed_samples: usize,
+    paused_since: Option<Instant>,
+    paused_total: Duration,
+    points: Vec<Point>,
+}
+
+impl MatchStats {
+    pub fn new() -> Self {
+        Self {
+            started: false,
+            elapsed: Duration::ZERO,
+            running_since: None,
+            pause_requested: false,
+            paused_by: None,
+            last_ball: None,
+            direction_x: 0.0,
+            score: (0, 0),
+            rally: 0,
+            longest_rally: 0,
+            ball_speed: None,
+            speed_sum: 0.0,
+            speed_samples: 0,
+            paused_since: None,
+            paused_total: Duration::ZERO,
+            points: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, state: &GameStateEvent) {
+        self.update_at(state, Instant::now());
+    }
+
+    fn update_at(&mut self, state: &GameStateEvent, now: Instant) {
+        if !self.started {
+            self.started = true;
+            self.running_since = Some(now);
+        }
+
+        if self.record_points((state.owner_score, state.guest_score)) {
+            self.last_ball = Some((state.ball_x, state.ball_y, now));
+            return;
+        }
+
+        if let Some((x, y, since)) = self.last_ball {
+            let (dx, dy) = (state.ball_x - x, state.ball_y - y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            let seconds = now.duration_since(since).as_secs_f64();
+
+            if distance > MAX_BALL_STEP {
+                self.direction_x = 0.0;
+            } else if distance > 0.0 && seconds > 0.0 {
+                let speed = distance / seconds;
+                self.speed_sum += speed;
+                self.speed_samples += 1;
+                self.ball_speed = Some(match self.ball_speed {
+                    Some(previous) => previous + (speed - previous) * SPEED_SMOOTHING,
+                    None => speed,
+                });
+            }
+
+            if distance <= MAX_BALL_STEP && dx != 0.0 {
+                if self.direction_x != 0.0 && dx.signum() != self.direction_x {
+                    self.rally += 1;
+                    self.longest_rally = self.longest_rally.max(self.rally);
+                }
+                self.direction_x = dx.signum();
+            }
+        }
+
+        self.last_ball = Some((state.ball_x, state.ball_y, now));
+    }
+
+    /// Adds a point for every score change, returns whether the score changed.
+    fn record_points(&mut self, score: (usize, usize)) -> bool {
+        if score == self.score {
+            return false;
+        }
+
+        let at = self.elapsed();
+        for _ in self.score.0..score.0 {
+            self.points.push(Point { owner_scored: true, at, rally: self.rally });
+        }
+        for _ in self.score.1..score.1 {
+            self.points.push(Point { owner_scored: false, at, rally: self.rally });
+        }
+
+        self.score = score;
+        self.rally = 0;
+        self.direction_x = 0.0;
+        true
+    }
+
+    /// Records the points of the final score, the last one is only reported by `game_over`.
+    pub fn finish(&mut self, owner_score: usize, guest_score: usize) {
+        self.record_points((owner_score, guest_score));
+        self.set_paused(false);
+        if let Some(since) = self.running_since.take() {
+            self.elapsed += since.elapsed();
+        }
+    }
+
+    /// Remembers that this client asked for the pause, so the next pause state
+    /// can be attributed.
+    pub fn request_pause(&mut self) {
+        self.pause_requested = true;
+    }
+
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.set_paused_at(is_paused, Instant::now());
+    }
+
+    fn set_paused_at(&mut self, is_paused: bool, now: Instant) {
+        match is_paused {
+            true => {
+                if let Some(since) = self.running_since.take() {
+                    self.elapsed += now.duration_since(since);
+                }
+                self.paused_since.get_or_insert(now);
+                self.paused_by = Some(match self.pause_requested {
+                    true => PausedBy::Me,
+                    false => PausedBy::Opponent,
+                });
+            }
+            false => {
+                if self.started && self.running_since.is_none() {
+                    self.running_since = Some(now);
+                }
+                if let Some(since) = self.paused_since.take() {
+                    self.paused_total += now.duration_since(since);
+                }
+                self.paused_by = None;
+            }
+        }
+        self.pause_requested = false;
+        // The ball stands still while paused, that must not count into the speed
+        self.last_ball = None;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.running_since.map(|since| since.elapsed()).unwrap_or_default()
+    }
+
+    pub fn rally(&self) -> usize {
+        self.rally
+    }
+
+    pub fn longest_rally(&self) -> usize {
+        self.longest_rally
+    }
+
+    pub fn ball_speed(&self) -> Option<f64> {
+        self.ball_speed
+    }
+
+    pub fn paused_by(&self) -> Option<PausedBy> {
+        self.paused_by
+    }
+
+    pub fn average_speed(&self) -> Option<f64> {
+        match self.speed_samples {
+            0 => None,
+            samples => Some(self.speed_sum / samples as f64),
+        }
+    }
+
+    /// Length of the pause that is going on right now.
+    pub fn current_pause(&self) -> Option<Duration> {
+        self.paused_since.map(|since| since.elapsed())
+    }
+
+    pub fn paused_time(&self) -> Duration {
+        self.paused_total + self.paused_since.map(|since| since.elapsed()).unwrap_or_default()
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(ball_x: f64, ball_y: f64, score: (usize, usize)) -> GameStateEvent {
+        GameStateEvent {
+            ball_x,
+            ball_y,
+            paddle1_y: 250.0,
+            paddle2_y: 250.0,
+            owner_score: score.0,
+            guest_score: score.1,
+        }
+    }
+
+    /// Feeds `states` a tenth of a second apart, starting at `start`.
+    fn feed(stats: &mut MatchStats, start: Instant, states: &[GameStateEvent]) -> Instant {
+        let mut now = start;
+        for state in states {
+            stats.update_at(state, now);
+            now += Duration::from_millis(100);
+        }
+        now
+    }
+
+    #[test]
+    fn rallies_count_direction_changes() {
+        let mut stats = MatchStats::new();
+        feed(
+            &mut stats,
+            Instant::now(),
+            &[
+                state(400.0, 300.0, (0, 0)),
+                state(450.0, 300.0, (0, 0)),
+                state(500.0, 300.0, (0, 0)),
+                // Bounced off the right paddle
+                state(450.0, 300.0, (0, 0)),
+                state(400.0, 300.0, (0, 0)),
+                // And off the left one
+                state(450.0, 300.0, (0, 0)),
+            ],
+        );
+
+        assert_eq!(stats.rally(), 2);
+        assert_eq!(stats.longest_rally(), 2);
+        assert!(stats.points().is_empty());
+    }
+
+    #[test]
+    fn point_mid_rally_records_and_resets_it() {
+        let mut stats = MatchStats::new();
+        feed(
+            &mut stats,
+            Instant::now(),
+            &[
+                state(400.0, 300.0, (0, 0)),
+                state(450.0, 300.0, (0, 0)),
+                state(400.0, 300.0, (0, 0)),
+                state(350.0, 300.0, (0, 0)),
+                state(400.0, 300.0, (0, 0)),
+                // The guest scores, the ball is back in the middle
+                state(400.0, 300.0, (0, 1)),
+                state(450.0, 300.0, (0, 1)),
+                // Jumping across the field is a reset, not a bounce
+                state(100.0, 300.0, (0, 1)),
+                state(50.0, 300.0, (0, 1)),
+            ],
+        );
+
+        let points = stats.points();
+        assert_eq!(points.len(), 1);
+        assert!(!points[0].owner_scored);
+        assert_eq!(points[0].rally, 2);
+        assert_eq!(stats.rally(), 0);
+        assert_eq!(stats.longest_rally(), 2);
+    }
+
+    #[test]
+    fn average_speed_skips_resets_and_pauses() {
+        let mut stats = MatchStats::new();
+        let start = Instant::now();
+        // 30 px every 100 ms, diagonally, is 500 px/s
+        let now = feed(
+            &mut stats,
+            start,
+            &[state(400.0, 300.0, (0, 0)), state(430.0, 340.0, (0, 0)), state(460.0, 380.0, (0, 0))],
+        );
+
+        stats.set_paused_at(true, now);
+        let resumed = now + Duration::from_secs(3);
+        stats.set_paused_at(false, resumed);
+
+        // The first state after the pause has no previous one to compare with
+        feed(
+            &mut stats,
+            resumed,
+            &[state(460.0, 380.0, (0, 0)), state(490.0, 420.0, (0, 0)), state(800.0, 300.0, (0, 0))],
+        );
+
+        let average = stats.average_speed().unwrap();
+        assert!((average - 500.0).abs() < 1e-6, "{}", average);
+        assert_eq!(stats.paused_time(), Duration::from_secs(3));
+        assert_eq!(stats.paused_by(), None);
+    }
+
+    #[test]
+    fn pause_is_attributed_to_the_requester() {
+        let mut stats = MatchStats::new();
+        let start = Instant::now();
+        feed(&mut stats, start, &[state(400.0, 300.0, (0, 0))]);
+
+        stats.request_pause();
+        stats.set_paused_at(true, start + Duration::from_secs(1));
+        assert_eq!(stats.paused_by(), Some(PausedBy::Me));
+        stats.set_paused_at(false, start + Duration::from_secs(2));
+
+        stats.set_paused_at(true, start + Duration::from_secs(3));
+        assert_eq!(stats.paused_by(), Some(PausedBy::Opponent));
+        stats.set_paused_at(false, start + Duration::from_secs(5));
+
+        assert_eq!(stats.paused_time(), Duration::from_secs(3));
+        assert_eq!(stats.average_speed(), None);
+    }
+}
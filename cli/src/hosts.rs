@@ -0,0 +1,215 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::time;
+
+use crate::{
+    api::{ApiClient, ApiError, endpoints},
+    config::{config_dir, write_atomic},
+    ui::pages::LoginType,
+};
+
+/// Hosts kept besides the bookmarks, older ones are dropped.
+const RECENT_LIMIT: usize = 8;
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PreferredLogin {
+    #[default]
+    #[serde(rename = "local")]
+    Local,
+    #[serde(rename = "42")]
+    Remote,
+}
+
+impl PreferredLogin {
+    pub fn login_type(self) -> LoginType {
+        match self {
+            Self::Local => LoginType::LocalLogin,
+            Self::Remote => LoginType::RemoteLogin,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Remote => "42",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostEntry {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub login: PreferredLogin,
+    #[serde(default)]
+    pub bookmarked: bool,
+    /// Seconds since the epoch of the last time the host was picked.
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+impl HostEntry {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// Reachability of a host, from the public `/api/health` route.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Health {
+    Checking,
+    Up(Duration),
+    Failing(u16),
+    Down,
+}
+
+/// Recently used and bookmarked hosts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HostStore {
+    #[serde(default)]
+    hosts: Vec<HostEntry>,
+}
+
+#[derive(Debug)]
+pub enum HostErrors {
+    NoConfigDir,
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl Display for HostErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoConfigDir => write!(f, "No config directory to store the hosts in"),
+            Self::Io(path, err) => write!(f, "Unable to access {}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "Invalid hosts file {}: {}", path.display(), err),
+            Self::Serialize(err) => write!(f, "Unable to serialize hosts: {}", err),
+        }
+    }
+}
+
+impl Error for HostErrors {}
+
+impl HostStore {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("hosts.toml"))
+    }
+
+    pub fn load() -> Result<Self, HostErrors> {
+        let path = Self::path().ok_or(HostErrors::NoConfigDir)?;
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(HostErrors::Io(path, err)),
+        };
+
+        toml::from_str(&content).map_err(|err| HostErrors::Parse(path, err))
+    }
+
+    pub fn save(&self) -> Result<(), HostErrors> {
+        let path = Self::path().ok_or(HostErrors::NoConfigDir)?;
+        let content = toml::to_string(self).map_err(HostErrors::Serialize)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| HostErrors::Io(dir.to_path_buf(), err))?;
+        }
+        write_atomic(&path, &content).map_err(|err| HostErrors::Io(path, err))
+    }
+
+    /// Bookmarks in the order they were added, then the other hosts from the
+    /// most recently used.
+    pub fn list(&self) -> Vec<HostEntry> {
+        let mut recent: Vec<HostEntry> = self.hosts.iter().filter(|host| !host.bookmarked).cloned().collect();
+        recent.sort_by_key(|host| std::cmp::Reverse(host.last_used));
+
+        self.hosts
+            .iter()
+            .filter(|host| host.bookmarked)
+            .cloned()
+            .chain(recent)
+            .collect()
+    }
+
+    /// Records that `url` was picked, a login or signup also becomes its
+    /// preferred login.
+    pub fn used(&mut self, url: &str, login_type: LoginType) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        let login = match login_type {
+            LoginType::RemoteLogin => PreferredLogin::Remote,
+            LoginType::LocalLogin | LoginType::Signup => PreferredLogin::Local,
+        };
+
+        match self.hosts.iter_mut().find(|host| host.url == url) {
+            Some(host) => {
+                host.last_used = now;
+                host.login = login;
+            }
+            None => self.hosts.push(HostEntry {
+                url: url.to_string(),
+                name: None,
+                login,
+                bookmarked: false,
+                last_used: now,
+            }),
+        }
+        self.prune();
+    }
+
+    pub fn toggle_bookmark(&mut self, url: &str) {
+        if let Some(host) = self.hosts.iter_mut().find(|host| host.url == url) {
+            host.bookmarked = !host.bookmarked;
+        }
+        self.prune();
+    }
+
+    pub fn rename(&mut self, url: &str, name: Option<String>) {
+        if let Some(host) = self.hosts.iter_mut().find(|host| host.url == url) {
+            host.name = name;
+        }
+    }
+
+    pub fn remove(&mut self, url: &str) {
+        self.hosts.retain(|host| host.url != url);
+    }
+
+    fn prune(&mut self) {
+        let mut recent: Vec<u64> = self
+            .hosts
+            .iter()
+            .filter(|host| !host.bookmarked)
+            .map(|host| host.last_used)
+            .collect();
+        if recent.len() <= RECENT_LIMIT {
+            return;
+        }
+        recent.sort_unstable_by(|a, b| b.cmp(a));
+        let oldest_kept = recent[RECENT_LIMIT - 1];
+        self.hosts.retain(|host| host.bookmarked || host.last_used >= oldest_kept);
+    }
+}
+
+/// Asks `/api/health` of the host of `api`. Any answer of the server counts as
+/// up, only error statuses and unreachable hosts don't.
+pub async fn health(api: &ApiClient) -> Health {
+    let started = Instant::now();
+    match time::timeout(HEALTH_TIMEOUT, api.call(&endpoints::Health {})).await {
+        Ok(Ok(_)) | Ok(Err(ApiError::InvalidResponse)) => Health::Up(started.elapsed()),
+        Ok(Err(ApiError::Status(status, _))) => Health::Failing(status.as_u16()),
+        Ok(Err(_)) | Err(_) => Health::Down,
+    }
+}
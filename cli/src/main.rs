@@ -1,39 +1,116 @@
+mod api;
 mod app;
 mod auth;
+mod capabilities;
+mod cli;
+mod config;
+mod credentials;
 mod game;
+mod hosts;
+mod server;
+mod session;
 mod types;
 mod ui;
+mod validation;
 mod websocket;
 
 use app::{App, FatalErrors};
+use capabilities::Capabilities;
+use clap::Parser;
+use cli::Cli;
+use config::Config;
+use server::Server;
 use crossterm::{
-    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::supports_keyboard_enhancement,
 };
 
 #[tokio::main]
 async fn main() -> Result<(), FatalErrors> {
-    let mut terminal = ratatui::init();
+    let cli = Cli::parse();
+    // Config mistakes are reported with the offending key, not as a debug dump
+    let config = Config::load().and_then(|mut config| cli.configure(&mut config).map(|()| config));
+    let config = match config {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
 
-    let kitty_protocol_support =
-        supports_keyboard_enhancement().map_err(FatalErrors::KeyboardEnhancementFlagsError)?;
+    let mut capabilities = Capabilities::detect();
+    capabilities.graphics = config.graphics.select(capabilities.graphics);
 
-    if kitty_protocol_support {
-        execute!(
-            terminal.backend_mut(),
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
-        )
-        .map_err(FatalErrors::KeyboardEnhancementFlagsError)?;
+    if cli.doctor {
+        println!("{}", capabilities.report());
+        return Ok(());
     }
 
-    let ret = App::new(kitty_protocol_support).run(&mut terminal).await;
+    let host = config
+        .host()
+        .map(Server::parse)
+        .transpose()
+        .map_err(FatalErrors::InvalidHost)?;
+
+    let theme = config
+        .theme()
+        .map_err(FatalErrors::ConfigError)?
+        .adapt(&capabilities);
+
+    // Only the TUI uses the keymap, but a typo should fail before any command runs
+    config.keymap().map_err(FatalErrors::ConfigError)?;
+
+    let start = match cli.command {
+        Some(command) => match cli::run(command, &config, cli.credentials.as_deref()).await {
+            Ok(Some(start)) => Some(start),
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
-    if kitty_protocol_support {
-        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)
+    let mut terminal = ratatui::init();
+    // The hook of ratatui::init restores the terminal but leaves mouse capture on
+    let restore_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        execute!(std::io::stdout(), DisableMouseCapture).ok();
+        restore_hook(info);
+    }));
+
+    let kitty_protocol_support = capabilities.kitty_keyboard;
+
+    let ret = async {
+        execute!(terminal.backend_mut(), EnableMouseCapture)
+            .map_err(FatalErrors::MouseCaptureError)?;
+        if kitty_protocol_support {
+            execute!(
+                terminal.backend_mut(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )
             .map_err(FatalErrors::KeyboardEnhancementFlagsError)?;
+        }
+        App::new(capabilities, config, theme, host, start)?
+            .run(&mut terminal)
+            .await
     }
+    .await;
+
+    // Runs even when the setup above failed halfway, the shell must not keep
+    // getting mouse events
+    let popped = match kitty_protocol_support {
+        true => execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)
+            .map_err(FatalErrors::KeyboardEnhancementFlagsError),
+        false => Ok(()),
+    };
+    let released = execute!(terminal.backend_mut(), DisableMouseCapture)
+        .map_err(FatalErrors::MouseCaptureError);
     ratatui::restore();
 
-    ret
+    ret.and(popped).and(released)
 }
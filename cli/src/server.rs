@@ -0,0 +1,160 @@
+use std::{error::Error, fmt::Display};
+
+use url::Url;
+
+/// Port of the web application firewall, the public entry of a deployment.
+const DEFAULT_PORT: u16 = 8443;
+
+/// Base URL of a deployment, the REST and socket.io endpoints live below it.
+/// Accepts anything from a bare host name to a full URL with scheme, port,
+/// IPv6 literal and path prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Server {
+    url: Url,
+}
+
+#[derive(Debug)]
+pub enum ServerErrors {
+    Empty,
+    Invalid(String),
+    UnsupportedScheme(String),
+    NoHost,
+    HasQuery,
+}
+
+impl Display for ServerErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Host can't be empty"),
+            Self::Invalid(err) => write!(f, "Invalid server address: {}", err),
+            Self::UnsupportedScheme(scheme) => write!(f, "Unsupported scheme {}, use https or http", scheme),
+            Self::NoHost => write!(f, "The server address has no host"),
+            Self::HasQuery => write!(f, "The server address can't have a query or fragment"),
+        }
+    }
+}
+
+impl Error for ServerErrors {}
+
+impl Server {
+    pub fn parse(input: &str) -> Result<Self, ServerErrors> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(ServerErrors::Empty);
+        }
+
+        let mut url = match input.contains("://") {
+            true => Url::parse(input),
+            false => Url::parse(&format!("https://{}", input)),
+        }
+        .map_err(|err| ServerErrors::Invalid(err.to_string()))?;
+
+        if !matches!(url.scheme(), "https" | "http") {
+            return Err(ServerErrors::UnsupportedScheme(url.scheme().to_string()));
+        }
+        if url.host().is_none() {
+            return Err(ServerErrors::NoHost);
+        }
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(ServerErrors::HasQuery);
+        }
+        if url.port().is_none() && !input.contains("://") {
+            url.set_port(Some(DEFAULT_PORT)).map_err(|_| ServerErrors::NoHost)?;
+        }
+
+        let prefix = url.path().trim_end_matches('/').to_string();
+        url.set_path(&prefix);
+        Ok(Self { url })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.url.as_str().trim_end_matches('/')
+    }
+
+    /// Scheme, host and port, as browsers send them in the Origin header.
+    pub fn origin(&self) -> String {
+        self.url.origin().ascii_serialization()
+    }
+
+    /// URL of the REST route `path`, which starts with a slash.
+    pub fn rest(&self, path: &str) -> String {
+        format!("{}{}", self.as_str(), path)
+    }
+
+    /// socket.io endpoint below `namespace` ("" for the game server),
+    /// authenticated with `token`.
+    pub fn socket(&self, namespace: &str, token: &str) -> String {
+        let mut url = self.url.clone();
+        // Only https and http get through parse, so the scheme is always valid
+        let scheme = match url.scheme() {
+            "http" => "ws",
+            _ => "wss",
+        };
+        url.set_scheme(scheme).ok();
+        url.set_path(&format!("{}/socket.io/{}", self.url.path().trim_end_matches('/'), namespace));
+        url.query_pairs_mut()
+            .append_pair("token", token)
+            .append_pair("EIO", "4")
+            .append_pair("transport", "websocket");
+        url.to_string()
+    }
+}
+
+impl Display for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_host_gets_default_port() {
+        let server = Server::parse("example.com").unwrap();
+        assert_eq!(server.as_str(), "https://example.com:8443");
+    }
+
+    #[test]
+    fn url_without_port_keeps_scheme_default() {
+        let server = Server::parse("https://example.com").unwrap();
+        assert_eq!(server.as_str(), "https://example.com");
+    }
+
+    #[test]
+    fn ipv6_literal_with_port() {
+        let server = Server::parse("[::1]:3000").unwrap();
+        assert_eq!(server.as_str(), "https://[::1]:3000");
+    }
+
+    #[test]
+    fn path_prefix_drops_trailing_slash() {
+        let server = Server::parse("https://example.com/pong/").unwrap();
+        assert_eq!(server.as_str(), "https://example.com/pong");
+        assert_eq!(server.rest("/api/verify"), "https://example.com/pong/api/verify");
+        assert_eq!(
+            server.socket("livechat", "abc"),
+            "wss://example.com/pong/socket.io/livechat?token=abc&EIO=4&transport=websocket"
+        );
+    }
+
+    #[test]
+    fn socket_scheme_follows_http_scheme() {
+        let server = Server::parse("http://localhost:3000").unwrap();
+        assert_eq!(
+            server.socket("", "abc"),
+            "ws://localhost:3000/socket.io/?token=abc&EIO=4&transport=websocket"
+        );
+
+        let server = Server::parse("https://localhost:3000").unwrap();
+        assert!(server.socket("", "abc").starts_with("wss://localhost:3000/socket.io/?"));
+    }
+
+    #[test]
+    fn rejects_query_and_fragment() {
+        for input in ["example.com?room=1", "https://example.com/#lobby"] {
+            assert!(matches!(Server::parse(input), Err(ServerErrors::HasQuery)), "{}", input);
+        }
+    }
+}
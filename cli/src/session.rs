@@ -0,0 +1,194 @@
+use std::{collections::HashMap, error::Error, fmt::Display, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{auth, config::config_dir};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+}
+
+impl Session {
+    /// Session of `token`, named after the nickname it carries.
+    pub fn new(token: String, refresh_token: Option<String>) -> Self {
+        Self {
+            nickname: auth::token_nickname(&token),
+            token,
+            refresh_token,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        self.nickname
+            .clone()
+            .or_else(|| auth::token_nickname(&self.token))
+            .unwrap_or("Unknown account".to_string())
+    }
+
+    /// Whether both sessions belong to the same user, tokens without a user
+    /// id only match themselves.
+    pub fn same_account(&self, other: &Session) -> bool {
+        match (auth::token_subject(&self.token), auth::token_subject(&other.token)) {
+            (Some(subject), Some(other_subject)) => subject == other_subject,
+            (_, _) => self.token == other.token,
+        }
+    }
+}
+
+/// Sessions of the hosts the user logged in to, kept in a file that only the
+/// user can read since the tokens grant full access to the accounts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionStore {
+    pub last_host: Option<String>,
+    /// Active account of each host.
+    #[serde(default)]
+    hosts: HashMap<String, Session>,
+    /// The other accounts saved for each host.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    accounts: HashMap<String, Vec<Session>>,
+}
+
+#[derive(Debug)]
+pub enum SessionErrors {
+    NoConfigDir,
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl Display for SessionErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoConfigDir => write!(f, "No config directory to store the session in"),
+            Self::Io(path, err) => write!(f, "Unable to access {}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "Invalid session file {}: {}", path.display(), err),
+            Self::Serialize(err) => write!(f, "Unable to serialize sessions: {}", err),
+        }
+    }
+}
+
+impl Error for SessionErrors {}
+
+impl SessionStore {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("sessions.toml"))
+    }
+
+    pub fn load() -> Result<Self, SessionErrors> {
+        let path = Self::path().ok_or(SessionErrors::NoConfigDir)?;
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(SessionErrors::Io(path, err)),
+        };
+
+        toml::from_str(&content).map_err(|err| SessionErrors::Parse(path, err))
+    }
+
+    pub fn save(&self) -> Result<(), SessionErrors> {
+        let path = Self::path().ok_or(SessionErrors::NoConfigDir)?;
+        let content = toml::to_string(self).map_err(SessionErrors::Serialize)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| SessionErrors::Io(dir.to_path_buf(), err))?;
+        }
+        write_private(&path, &content).map_err(|err| SessionErrors::Io(path, err))
+    }
+
+    pub fn get(&self, host: &str) -> Option<&Session> {
+        self.hosts.get(host)
+    }
+
+    /// Every account saved for `host`, the active one first.
+    pub fn accounts(&self, host: &str) -> Vec<Session> {
+        self.hosts
+            .get(host)
+            .into_iter()
+            .chain(self.accounts.get(host).into_iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    /// Makes the session the active account of `host`, the account that was
+    /// active before stays saved unless it is the same user.
+    pub fn insert(&mut self, host: &str, token: &str, refresh_token: Option<&str>) {
+        self.last_host = Some(host.to_string());
+        let session = Session::new(token.to_string(), refresh_token.map(str::to_string));
+
+        let others = self.accounts.entry(host.to_string()).or_default();
+        others.retain(|other| !other.same_account(&session));
+        if let Some(previous) = self.hosts.insert(host.to_string(), session)
+            && let Some(session) = self.hosts.get(host)
+            && !previous.same_account(session)
+        {
+            others.insert(0, previous);
+        }
+        if others.is_empty() {
+            self.accounts.remove(host);
+        }
+    }
+
+    /// Replaces the saved account whose refresh token was spent on `session`,
+    /// on whichever host it is. False when no account used that token.
+    pub fn renew(&mut self, spent: &str, session: Session) -> bool {
+        let saved = self
+            .hosts
+            .values_mut()
+            .chain(self.accounts.values_mut().flatten())
+            .find(|saved| saved.refresh_token.as_deref() == Some(spent));
+        match saved {
+            Some(saved) => {
+                *saved = session;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the active account of `host`, the others stay saved.
+    pub fn remove(&mut self, host: &str) -> Option<Session> {
+        self.hosts.remove(host)
+    }
+
+    /// Removes the account of `session` from `host`, active or not.
+    pub fn forget(&mut self, host: &str, session: &Session) {
+        if self.hosts.get(host).is_some_and(|active| active.token == session.token) {
+            self.hosts.remove(host);
+        }
+        if let Some(others) = self.accounts.get_mut(host) {
+            others.retain(|other| other.token != session.token);
+            if others.is_empty() {
+                self.accounts.remove(host);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &PathBuf, content: &str) -> io::Result<()> {
+    use std::{
+        io::Write,
+        os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    };
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // The mode only applies to new files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &PathBuf, content: &str) -> io::Result<()> {
+    fs::write(path, content)
+}
@@ -0,0 +1,446 @@
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::Paragraph,
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::{
+    api::endpoints::{AccountResponse, ProfileResponse},
+    validation,
+};
+
+use super::{pages::PageResults, theme::Theme};
+
+/// Changes requested on the account page. Local accounts confirm every change
+/// with their password, `reauth` holds the e-mail and password for the
+/// requests that don't check it on the server.
+#[derive(Debug, Clone)]
+pub enum AccountRequest {
+    Nickname {
+        nickname: String,
+        reauth: Option<(String, String)>,
+    },
+    Email {
+        email: String,
+        password: String,
+    },
+    Password {
+        email: String,
+        password: String,
+        new_password: String,
+    },
+    Delete {
+        password: Option<String>,
+        totp_code: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Nickname,
+    Email,
+    Password,
+    Delete,
+}
+
+impl Action {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Nickname => "Change Nickname",
+            Self::Email => "Change E-Mail",
+            Self::Password => "Change Password",
+            Self::Delete => "Delete Account",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldKind {
+    Nickname,
+    Email,
+    Password,
+    NewPassword,
+    Confirm,
+    TotpCode,
+    Confirmation,
+}
+
+impl FieldKind {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Nickname => "New Nickname",
+            Self::Email => "New E-Mail",
+            Self::Password => "Current Password",
+            Self::NewPassword => "New Password",
+            Self::Confirm => "Confirm New Password",
+            Self::TotpCode => "2FA Code",
+            Self::Confirmation => "Type your nickname to confirm",
+        }
+    }
+
+    fn max_length(&self) -> usize {
+        match self {
+            Self::Nickname | Self::Confirmation => validation::NICKNAME_LENGTH.1,
+            Self::Email => validation::EMAIL_MAX_LENGTH,
+            Self::Password | Self::NewPassword | Self::Confirm => validation::PASSWORD_LENGTH.1,
+            Self::TotpCode => 6,
+        }
+    }
+
+    fn secret(&self) -> bool {
+        matches!(self, Self::Password | Self::NewPassword | Self::Confirm)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Form {
+    action: Action,
+    fields: Vec<(FieldKind, Input)>,
+    selected: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountPage {
+    account: Option<AccountResponse>,
+    profile: Option<ProfileResponse>,
+    actions: Vec<Action>,
+    selected: usize,
+    form: Option<Form>,
+    pending: bool,
+    needs_update: bool,
+    error_message: Option<String>,
+    info_message: Option<String>,
+}
+
+impl AccountPage {
+    pub fn new() -> Self {
+        Self {
+            account: None,
+            profile: None,
+            actions: Vec::new(),
+            selected: 0,
+            form: None,
+            pending: true,
+            needs_update: true,
+            error_message: None,
+            info_message: None,
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.account
+            .as_ref()
+            .is_some_and(|account| account.auth_method == "local")
+    }
+
+    fn totp_enabled(&self) -> bool {
+        self.account.as_ref().is_some_and(|account| account.totp_enabled)
+    }
+
+    fn email(&self) -> String {
+        self.account
+            .as_ref()
+            .and_then(|account| account.email.clone())
+            .or_else(|| self.profile.as_ref()?.email.clone())
+            .unwrap_or_default()
+    }
+
+    fn nickname(&self) -> Option<&str> {
+        self.profile.as_ref()?.nickname.as_deref()
+    }
+
+    pub fn set_account(&mut self, account: AccountResponse) {
+        self.account = Some(account);
+        // Without a password the server can't confirm e-mail or password changes
+        self.actions = match self.is_local() {
+            true => vec![Action::Nickname, Action::Email, Action::Password, Action::Delete],
+            false => vec![Action::Nickname, Action::Delete],
+        };
+        self.selected = self.selected.min(self.actions.len() - 1);
+        self.pending = self.profile.is_none();
+        self.needs_update = true;
+    }
+
+    pub fn set_profile(&mut self, profile: ProfileResponse) {
+        self.profile = Some(profile);
+        self.pending = self.account.is_none();
+        self.needs_update = true;
+    }
+
+    fn open_form(&mut self, action: Action) {
+        let password = self.is_local().then_some(FieldKind::Password);
+        let kinds: Vec<FieldKind> = match action {
+            Action::Nickname => [Some(FieldKind::Nickname), password].into_iter().flatten().collect(),
+            Action::Email => vec![FieldKind::Email, FieldKind::Password],
+            Action::Password => vec![FieldKind::Password, FieldKind::NewPassword, FieldKind::Confirm],
+            Action::Delete => [
+                Some(FieldKind::Confirmation),
+                password,
+                self.totp_enabled().then_some(FieldKind::TotpCode),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        };
+
+        self.form = Some(Form {
+            action,
+            fields: kinds.into_iter().map(|kind| (kind, Input::default())).collect(),
+            selected: 0,
+        });
+        self.error_message = None;
+        self.info_message = None;
+        self.needs_update = true;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(46),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let rows = match &self.form {
+            Some(form) => form.fields.len(),
+            None => self.actions.len(),
+        };
+        let [details, body, message] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Length(3 * rows as u16),
+            Constraint::Length(3),
+        ])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+        self.render_details(frame, theme, details);
+
+        let areas = Layout::vertical(vec![Constraint::Length(3); rows]).split(body);
+        match &self.form {
+            Some(form) => {
+                for (index, ((kind, input), area)) in form.fields.iter().zip(areas.iter()).enumerate() {
+                    render_input(frame, theme, *area, *kind, input, index == form.selected && !self.pending);
+                }
+            }
+            None => {
+                for (index, (action, area)) in self.actions.iter().zip(areas.iter()).enumerate() {
+                    let style = theme.selection(index == self.selected);
+                    let option = Paragraph::new(action.label())
+                        .style(style)
+                        .block(theme.block().border_style(style));
+                    frame.render_widget(option, *area);
+                }
+            }
+        }
+
+        if let Some(msg) = &self.error_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.error))
+                .block(theme.block().title("Error"));
+            frame.render_widget(widget, message);
+        } else if let Some(msg) = &self.info_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.text))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        }
+
+        self.needs_update = false;
+    }
+
+    fn render_details(&self, frame: &mut Frame, theme: &Theme, area: Rect) {
+        let title = match &self.form {
+            Some(form) => form.action.label(),
+            None => "Account",
+        };
+        let block = theme.block().title(title).title_bottom("Esc: back");
+
+        let lines = match (&self.account, self.pending) {
+            (Some(account), false) => {
+                let login = match account.auth_method.as_str() {
+                    "local" => "e-mail and password",
+                    _ => "42",
+                };
+                let totp = match account.totp_enabled {
+                    true => "on",
+                    false => "off",
+                };
+                vec![
+                    Line::styled(format!("Nickname: {}", self.nickname().unwrap_or("-")), theme.text),
+                    Line::styled(format!("E-Mail:   {}", self.email()), theme.text),
+                    Line::styled(format!("Login:    {}", login), theme.text),
+                    Line::styled(format!("2FA:      {}", totp), theme.text),
+                ]
+            }
+            (_, true) => vec![Line::styled("Loading...", theme.muted)],
+            (None, false) => vec![Line::styled("Account details are unavailable", theme.muted)],
+        };
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if self.pending && key.code != KeyCode::Esc {
+            return None;
+        }
+
+        let Some(form) = self.form.as_mut() else {
+            match key.code {
+                KeyCode::Esc => return Some(PageResults::BackToMenu),
+                KeyCode::Up | KeyCode::BackTab => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Tab if !self.actions.is_empty() => {
+                    self.selected = (self.selected + 1).min(self.actions.len() - 1)
+                }
+                KeyCode::Enter => {
+                    if let Some(action) = self.actions.get(self.selected).copied() {
+                        self.open_form(action);
+                    }
+                }
+                _ => return None,
+            }
+            self.needs_update = true;
+            return None;
+        };
+
+        let (kind, input) = &mut form.fields[form.selected];
+        match key.code {
+            KeyCode::Esc => {
+                self.form = None;
+                self.error_message = None;
+            }
+            KeyCode::Char(c) if input.value().len() < kind.max_length() && c.is_ascii_graphic() => {
+                input.handle_event(event);
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Right => {
+                input.handle_event(event);
+            }
+            KeyCode::Up | KeyCode::BackTab => form.selected = form.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => {
+                form.selected = (form.selected + 1).min(form.fields.len() - 1)
+            }
+            KeyCode::Enter if form.selected + 1 < form.fields.len() => form.selected += 1,
+            KeyCode::Enter => return self.submit(),
+            _ => return None,
+        }
+        self.needs_update = true;
+        None
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        let form = self.form.as_ref()?;
+        let value = |kind: FieldKind| {
+            form.fields
+                .iter()
+                .find(|(field, _)| *field == kind)
+                .map(|(_, input)| input.value().to_owned())
+        };
+        let password = value(FieldKind::Password);
+        let reauth = password.clone().map(|password| (self.email(), password));
+
+        let request = match form.action {
+            Action::Nickname => {
+                let nickname = value(FieldKind::Nickname).unwrap_or_default();
+                validation::nickname(&nickname).map(|_| AccountRequest::Nickname { nickname, reauth })
+            }
+            Action::Email => {
+                let email = value(FieldKind::Email).unwrap_or_default();
+                validation::email(&email).map(|_| AccountRequest::Email {
+                    email,
+                    password: password.unwrap_or_default(),
+                })
+            }
+            Action::Password => {
+                let new_password = value(FieldKind::NewPassword).unwrap_or_default();
+                validation::password(&new_password)
+                    .and_then(|_| match value(FieldKind::Confirm) == Some(new_password.clone()) {
+                        true => Ok(()),
+                        false => Err("Passwords don't match".to_string()),
+                    })
+                    .map(|_| AccountRequest::Password {
+                        email: self.email(),
+                        password: password.unwrap_or_default(),
+                        new_password,
+                    })
+            }
+            Action::Delete => {
+                let expected = self.nickname().unwrap_or_default();
+                let totp_code = value(FieldKind::TotpCode);
+                match value(FieldKind::Confirmation).as_deref() == Some(expected) {
+                    _ if totp_code.as_ref().is_some_and(|code| code.len() != 6) => {
+                        Err("The 2FA code has 6 digits".to_string())
+                    }
+                    true => Ok(AccountRequest::Delete { password, totp_code }),
+                    false => Err(format!("Type \"{}\" to delete the account", expected)),
+                }
+            }
+        };
+
+        match request {
+            Ok(request) => {
+                self.pending = true;
+                self.error_message = None;
+                self.needs_update = true;
+                Some(PageResults::Account(request))
+            }
+            Err(message) => {
+                self.error(message);
+                None
+            }
+        }
+    }
+
+    /// A change went through, back to the overview while the details reload.
+    pub fn done(&mut self, message: String) {
+        self.form = None;
+        self.pending = true;
+        self.account = None;
+        self.profile = None;
+        self.info(message);
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.pending = false;
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    pub fn needs_update(&self) -> bool {
+        self.needs_update
+    }
+}
+
+fn render_input(frame: &mut Frame, theme: &Theme, rect: Rect, kind: FieldKind, input: &Input, focused: bool) {
+    let width = rect.width.saturating_sub(2) as usize;
+    let scroll = input.visual_scroll(width.saturating_sub(1));
+
+    let text = match kind.secret() {
+        true => "*".repeat(input.value().len()),
+        false => input.value().to_owned(),
+    };
+
+    let style = theme.selection(focused);
+    let paragraph = Paragraph::new(text)
+        .style(Style::from(theme.input))
+        .scroll((0, scroll as u16))
+        .block(theme.block().border_style(style).title(kind.title()));
+    frame.render_widget(paragraph, rect);
+
+    if focused {
+        let x = input.visual_cursor().saturating_sub(scroll) + 1;
+        frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
+    }
+}
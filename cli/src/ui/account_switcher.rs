@@ -0,0 +1,199 @@
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Margin, Position, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::session::Session;
+
+use super::{pages::PageResults, theme::Theme};
+
+/// Rows of the account list, more accounts scroll.
+const LIST_ROWS: usize = 8;
+
+/// Choices of the account switcher, carried out by the app since it owns the
+/// sessions.
+#[derive(Debug)]
+pub enum AccountChoice {
+    Use(Session),
+    Add,
+    Forget(Session),
+    Back,
+}
+
+/// Saved accounts of a host, one of them is used without logging in again.
+#[derive(Debug, Clone)]
+pub struct AccountSwitcherPage {
+    host: String,
+    accounts: Vec<Session>,
+    /// Token of the account in use, marked in the list.
+    active: Option<String>,
+    /// The row after the accounts logs in with another one.
+    selection: usize,
+    list_area: Rect,
+    needs_update: bool,
+    error_message: Option<String>,
+    info_message: Option<String>,
+}
+
+impl AccountSwitcherPage {
+    pub fn new(host: String, accounts: Vec<Session>, active: Option<String>) -> Self {
+        Self {
+            host,
+            accounts,
+            active,
+            selection: 0,
+            list_area: Rect::default(),
+            needs_update: true,
+            error_message: None,
+            info_message: None,
+        }
+    }
+
+    pub fn set_accounts(&mut self, accounts: Vec<Session>) {
+        self.accounts = accounts;
+        self.selection = self.selection.min(self.accounts.len());
+        self.needs_update = true;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let rows = self.accounts.len() + 1;
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(48),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let [list, message] = Layout::vertical([
+            Constraint::Length(rows.min(LIST_ROWS) as u16 + 2),
+            Constraint::Length(3),
+        ])
+        .flex(Flex::Center)
+        .areas(horizontal);
+        self.list_area = list;
+
+        let offset = self.selection.saturating_sub(LIST_ROWS - 1);
+        let add = Line::from(Span::styled(
+            "+ Log in with another account",
+            match self.selection == self.accounts.len() {
+                true => theme.selection(true),
+                false => Style::from(theme.muted),
+            },
+        ));
+        let lines: Vec<Line> = self
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(index, session)| {
+                let style = match index == self.selection {
+                    true => theme.selection(true),
+                    false => Style::from(theme.text),
+                };
+                let active = self.active.as_deref() == Some(session.token.as_str());
+                Line::from(vec![
+                    Span::styled(if active { "> " } else { "  " }, theme.accent),
+                    Span::styled(session.label(), style),
+                    Span::styled(if active { "  active" } else { "" }, theme.muted),
+                ])
+            })
+            .chain(std::iter::once(add))
+            .skip(offset)
+            .take(LIST_ROWS)
+            .collect();
+
+        let widget = Paragraph::new(lines).block(
+            theme
+                .block()
+                .title(format!("Accounts on {}", self.host))
+                .title_bottom("Enter: use  Del: forget  Esc: back"),
+        );
+        frame.render_widget(widget, list);
+
+        if let Some(msg) = &self.error_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.error))
+                .block(theme.block().title("Error"));
+            frame.render_widget(widget, message);
+        } else if let Some(msg) = &self.info_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.text))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        }
+
+        self.needs_update = false;
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        match key.code {
+            KeyCode::Esc => return Some(PageResults::Accounts(AccountChoice::Back)),
+            KeyCode::Enter => return Some(self.choose()),
+            KeyCode::Delete => {
+                return self
+                    .accounts
+                    .get(self.selection)
+                    .map(|session| PageResults::Accounts(AccountChoice::Forget(session.clone())));
+            }
+            KeyCode::Up => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => self.selection = (self.selection + 1).min(self.accounts.len()),
+            _ => return None,
+        }
+        self.needs_update = true;
+        None
+    }
+
+    pub fn mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        let inner = self.list_area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(event.column, event.row)) {
+            return None;
+        }
+        let offset = self.selection.saturating_sub(LIST_ROWS - 1);
+        let row = offset + (event.row - inner.y) as usize;
+        if row > self.accounts.len() {
+            return None;
+        }
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.selection = row;
+                Some(self.choose())
+            }
+            MouseEventKind::Moved if self.selection != row => {
+                self.selection = row;
+                self.needs_update = true;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn choose(&self) -> PageResults {
+        match self.accounts.get(self.selection) {
+            Some(session) => PageResults::Accounts(AccountChoice::Use(session.clone())),
+            None => PageResults::Accounts(AccountChoice::Add),
+        }
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    pub fn needs_update(&self) -> bool {
+        self.needs_update
+    }
+}
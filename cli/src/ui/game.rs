@@ -1,3 +1,4 @@
+use std::time::{Duration, Instant};
 
 use crate::game::movement::Movement;
 use crate::types::{Player, Position};
@@ -7,19 +8,30 @@ use crate::websocket::events::request::PaddleMoveDirection;
 use crate::websocket::events::websocketevents::{
     GameOverEvent, GameStartEvent, GameStartEventPlayer, GameStateEvent,
 };
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Layout, Rect};
-use ratatui::style::{Color, Style, Stylize};
-use ratatui::symbols::Marker;
+use ratatui::layout::{Constraint, Layout, Margin, Position as CellPosition, Rect};
+use ratatui::style::{Style, Stylize};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::canvas::Canvas;
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::Paragraph;
 
+use crate::capabilities::{self, Capabilities, Glyphs, GraphicsProtocol};
+use crate::config::{HudConfig, StartNotification};
+use crate::game::keymap::{Action, Keymap};
+use crate::game::stats::{MatchStats, PausedBy};
+
+use super::graphics::{self, Raster};
 use super::pages::PageResults;
+use super::pause_menu::{MenuAction, PauseMenu};
+use super::theme::Theme;
 use super::widgets::ball::Ball;
 use super::widgets::filledrectangle::FilledRectangle;
 
+const PADDLE_HEIGHT: f64 = 100.0;
+const MOUSE_TOLERANCE: f64 = 8.0;
+const FALLBACK_CELL_RATIO: f64 = 1.9;
+
 #[derive(Debug, PartialEq, Clone)]
 enum CurrentPlayer {
     PlayerA,
@@ -44,8 +56,23 @@ pub struct GameResultPlayer {
 #[derive(Debug, Clone)]
 pub struct GameResult {
     pub won: bool,
+    pub is_owner: bool,
     pub player_a: GameResultPlayer,
     pub player_b: GameResultPlayer,
+    pub stats: MatchStats,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameSettings {
+    pub hud: HudConfig,
+    pub keymap: Keymap,
+    pub theme: String,
+    pub themes: Vec<String>,
+    pub start_delay: Duration,
+    pub start_notification: StartNotification,
+    pub movement_timeout: Duration,
+    /// Nickname of the account in use, shown on its score panel.
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,24 +81,53 @@ pub struct Game {
     player_b: Player,
     current_player: CurrentPlayer,
     current_movement: Movement,
+    mouse_target: Option<f64>,
+    mouse_direction: PaddleMoveDirection,
     ball: Position,
     owner_score_widget: Rect,
     game_widget: Rect,
     guest_score_widget: Rect,
+    hud_widget: Rect,
     last_sizes: (u16, u16),
     needs_update: bool,
     is_starting: bool,
     is_paused: bool,
+    glyphs: Glyphs,
+    graphics: Option<GraphicsProtocol>,
+    pending_output: Vec<u8>,
+    stats: MatchStats,
+    hud: HudConfig,
+    show_hud: bool,
+    // Last image sent to the terminal, resent only when it changes
+    shown_image: Option<(Raster, Rect)>,
+    keymap: Keymap,
+    theme: String,
+    themes: Vec<String>,
+    menu: Option<PauseMenu>,
+    paused_by_menu: bool,
+    announced_at: Instant,
+    start_delay: Duration,
+    start_notification: StartNotification,
+    shown_timer: Option<u64>,
+    account: Option<String>,
 }
 
 impl Game {
-    pub fn new(start_event: GameStartEvent, frame: &Frame) -> Self {
-        let (owner_score_widget, game_widget, guest_score_widget) = setup_widgets(frame);
+    pub fn new(
+        start_event: GameStartEvent,
+        frame: &Frame,
+        capabilities: &Capabilities,
+        settings: GameSettings,
+    ) -> Self {
+        let (owner_score_widget, game_widget, guest_score_widget, hud_widget) =
+            setup_widgets(frame);
         Self {
             player_a: Player::new(start_event.owner, start_event.paddle1_y),
             player_b: Player::new(start_event.guest, start_event.paddle2_y),
             current_player: start_event.is_owner.into(),
-            current_movement: Movement::new(),
+            current_movement: Movement::new(settings.movement_timeout),
+            mouse_target: None,
+            mouse_direction: PaddleMoveDirection::None,
             ball: Position {
                 pos_y: start_event.ball_y,
                 pos_x: start_event.ball_x,
@@ -79,14 +135,42 @@ impl Game {
             owner_score_widget,
             game_widget,
             guest_score_widget,
+            hud_widget,
             last_sizes: (frame.area().width, frame.area().height),
             needs_update: true,
             is_starting: true,
             is_paused: false,
+            glyphs: capabilities.glyphs,
+            graphics: capabilities.graphics,
+            pending_output: Vec::new(),
+            stats: MatchStats::new(),
+            hud: settings.hud,
+            show_hud: true,
+            shown_image: None,
+            keymap: settings.keymap,
+            theme: settings.theme,
+            themes: settings.themes,
+            menu: None,
+            paused_by_menu: false,
+            announced_at: Instant::now(),
+            start_delay: settings.start_delay,
+            start_notification: settings.start_notification,
+            shown_timer: None,
+            account: settings.account,
         }
     }
 
-    pub fn update(&mut self, state_event: &GameStateEvent) {
+    /// Applies a `game_state`. For the first one it returns how long the server
+    /// took to start the match after announcing it.
+    pub fn update(&mut self, state_event: &GameStateEvent) -> Option<Duration> {
+        let start_delay = match self.is_starting {
+            true => {
+                self.pending_output.extend_from_slice(self.start_notification.sequence());
+                Some(self.announced_at.elapsed())
+            }
+            false => None,
+        };
+
         self.player_a.pos_y = state_event.paddle1_y;
         self.player_b.pos_y = state_event.paddle2_y;
         self.player_a.score = state_event.owner_score;
@@ -94,16 +178,34 @@ impl Game {
         self.ball.pos_y = state_event.ball_y;
         self.ball.pos_x = state_event.ball_x;
         self.is_starting = false;
+        self.stats.update(state_event);
         self.needs_update = true;
+        start_delay
     }
 
     pub fn set_paused(&mut self, is_paused: bool) {
       self.is_paused = is_paused;
+      self.stats.set_paused(is_paused);
       self.needs_update = true;
     }
 
-    pub async fn tick(&mut self, socket: &mut SocketIoClient) -> Result<(), EventError> {
-        if self.current_movement.movement_stopped() {
+    pub async fn tick(
+        &mut self,
+        socket: &mut SocketIoClient,
+        kitty_protocol_support: bool,
+    ) -> Result<(), EventError> {
+        if self.menu.is_some() && self.mouse_direction != PaddleMoveDirection::None {
+            self.mouse_target = None;
+            self.mouse_direction = PaddleMoveDirection::None;
+            socket
+                .paddle_move((PaddleMoveDirection::None, PaddleMoveDirection::None))
+                .await?;
+        } else if let Some(direction) = self.follow_mouse_target() {
+            socket.paddle_move(self.movements(direction)).await?;
+        } else if !kitty_protocol_support
+            && self.mouse_target.is_none()
+            && self.current_movement.movement_stopped()
+        {
             socket
                 .paddle_move((PaddleMoveDirection::None, PaddleMoveDirection::None))
                 .await?;
@@ -112,37 +214,174 @@ impl Game {
         Ok(())
     }
 
-    fn render_game(&self, frame: &mut Frame) {
+    /// Returns the direction the paddle has to move in to reach the mouse target,
+    /// but only if it differs from the direction that was sent last.
+    fn follow_mouse_target(&mut self) -> Option<PaddleMoveDirection> {
+        let target = self.mouse_target?;
+
+        let pos_y = match self.current_player {
+            CurrentPlayer::PlayerA => self.player_a.pos_y,
+            CurrentPlayer::PlayerB => self.player_b.pos_y,
+        };
+
+        let direction = match target - pos_y {
+            diff if diff > MOUSE_TOLERANCE => PaddleMoveDirection::Down,
+            diff if diff < -MOUSE_TOLERANCE => PaddleMoveDirection::Up,
+            _ => PaddleMoveDirection::None,
+        };
+
+        if direction == PaddleMoveDirection::None {
+            self.mouse_target = None;
+        }
+
+        if direction == self.mouse_direction {
+            return None;
+        }
+
+        self.mouse_direction = direction.clone();
+        Some(direction)
+    }
+
+    fn movements(
+        &self,
+        direction: PaddleMoveDirection,
+    ) -> (PaddleMoveDirection, PaddleMoveDirection) {
+        match self.current_player {
+            CurrentPlayer::PlayerA => (direction, PaddleMoveDirection::None),
+            CurrentPlayer::PlayerB => (PaddleMoveDirection::None, direction),
+        }
+    }
+
+    /// Seconds shown by the start countdown or the pause timer.
+    fn timer(&self) -> Option<u64> {
+        match (self.is_starting, self.is_paused) {
+            (true, _) => {
+                let remaining = self.start_delay.saturating_sub(self.announced_at.elapsed());
+                Some(remaining.as_secs() + (remaining.subsec_nanos() > 0) as u64)
+            }
+            (false, true) => self.stats.current_pause().map(|pause| pause.as_secs()),
+            (false, false) => None,
+        }
+    }
+
+    fn status_text(&self) -> Option<String> {
+        match (self.is_starting, self.is_paused, self.timer()) {
+            (true, _, Some(0)) | (true, _, None) => Some("Game will start soon".to_string()),
+            (true, _, Some(seconds)) => Some(format!("Game starts in {}", seconds)),
+            (false, true, Some(seconds)) => {
+                Some(format!("Game is paused ({:02}:{:02})", seconds / 60, seconds % 60))
+            }
+            (false, true, None) => Some("Game is paused".to_string()),
+            (false, false, _) => None,
+        }
+    }
+
+    /// Escape sequences from the last render and update, the caller writes them
+    /// to the terminal after the frame is flushed.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending_output)
+    }
+
+    fn render_pixels(
+        &mut self,
+        frame: &mut Frame,
+        theme: &Theme,
+        protocol: GraphicsProtocol,
+        mut raster: Raster,
+    ) {
+        let mut block = theme.block().title("Pong CLI");
+        if let Some(status) = self.status_text() {
+            block = block.title_bottom(Line::styled(status, theme.status).centered());
+        }
+        frame.render_widget(block, self.game_widget);
+
+        let area = self.game_widget.inner(Margin::new(1, 1));
+        let buffer = frame.buffer_mut();
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(cell) = buffer.cell_mut(CellPosition::new(x, y)) {
+                    cell.set_skip(true);
+                }
+            }
+        }
+
+        let line = raster.add_color(theme.muted);
+        for y in (0..600).step_by(40) {
+            raster.fill_rect(399.0, y as f64 + 10.0, 2.0, 20.0, line);
+        }
+
+        let paddle_a = raster.add_color(theme.paddle_a);
+        raster.fill_rect(0.0, self.player_a.pos_y, 10.0, PADDLE_HEIGHT, paddle_a);
+        let paddle_b = raster.add_color(theme.paddle_b);
+        raster.fill_rect(790.0, self.player_b.pos_y, 10.0, PADDLE_HEIGHT, paddle_b);
+
+        if !self.is_starting {
+            let ball = raster.add_color(theme.ball);
+            raster.fill_circle(self.ball.pos_x - 10.0, self.ball.pos_y, 10.0, ball);
+        }
+
+        let unchanged = self
+            .shown_image
+            .as_ref()
+            .is_some_and(|(shown, shown_area)| *shown == raster && *shown_area == area);
+        if !unchanged {
+            self.pending_output.extend(raster.encode(protocol, area));
+            self.shown_image = Some((raster, area));
+        }
+    }
+
+    /// Makes the next render send the image again, after the screen was cleared.
+    pub fn forget_image(&mut self) {
+        self.shown_image = None;
+    }
+
+    fn render_game(&mut self, frame: &mut Frame, theme: &Theme) {
+        let area = self.game_widget.inner(Margin::new(1, 1));
+        if let Some(protocol) = self.graphics
+            && self.menu.is_none()
+            && let Some(raster) = Raster::for_area(protocol, area)
+        {
+            return self.render_pixels(frame, theme, protocol, raster);
+        }
+
+        // Images are drawn above the text, so the overlay needs them gone
+        if let Some(protocol) = self.graphics
+            && self.shown_image.take().is_some()
+        {
+            self.pending_output.extend_from_slice(graphics::clear_sequence(protocol));
+        }
+
         let canvas = Canvas::default()
-            .block(Block::default().title("Pong CLI").borders(Borders::ALL))
+            .block(theme.block().title("Pong CLI"))
             .x_bounds([0.0, 800.0])
             .y_bounds([0.0, 600.0])
-            .marker(Marker::Braille)
+            .marker(self.glyphs.ball_marker())
             .paint(|ctx| {
                 if !self.is_starting {
                   ctx.draw(&Ball {
                       x: self.ball.pos_x - 10.0,
                       y: 600.0 - self.ball.pos_y,
                       radius: 10.0,
-                      color: Color::Rgb(255, 255, 0),
+                      color: theme.ball,
                   });
                 }
             });
 
         frame.render_widget(canvas, self.game_widget);
+        self.replace_blocks(frame, "O");
 
         let canvas = Canvas::default()
-            .block(Block::default().title("Pong CLI").borders(Borders::ALL))
+            .block(theme.block().title("Pong CLI"))
             .x_bounds([0.0, 800.0])
             .y_bounds([0.0, 600.0])
-            .marker(Marker::HalfBlock)
+            .marker(self.glyphs.paddle_marker())
             .paint(|ctx| {
                 ctx.draw(&FilledRectangle {
                     x: 0.0,
                     y: 500.0 - self.player_a.pos_y,
                     width: 10.0,
                     height: 100.0,
-                    color: Color::Rgb(255, 0, 255),
+                    color: theme.paddle_a,
                 });
 
                 ctx.draw(&FilledRectangle {
@@ -150,36 +389,63 @@ impl Game {
                     y: 500.0 - self.player_b.pos_y,
                     width: 10.0,
                     height: 100.0,
-                    color: Color::Rgb(0, 255, 255),
+                    color: theme.paddle_b,
                 });
 
-                if self.is_starting {
-                  ctx.print(350.0, 300.0, "Game will start soon".yellow());
-                }
-                else if self.is_paused {
-                  ctx.print(350.0, 300.0, "Game is paused".yellow());
+                if let Some(status) = self.status_text() {
+                  ctx.print(350.0, 300.0, Span::styled(status, theme.status));
                 }
             });
 
         frame.render_widget(canvas, self.game_widget);
+        self.replace_blocks(frame, "#");
+    }
+
+    /// Without Unicode support the canvas is drawn with full blocks, those get
+    /// swapped for an ASCII character after each layer is rendered.
+    fn replace_blocks(&self, frame: &mut Frame, symbol: &str) {
+        if self.glyphs != Glyphs::Ascii {
+            return;
+        }
+
+        let area = self.game_widget.inner(Margin::new(1, 1));
+        let buffer = frame.buffer_mut();
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buffer.cell_mut(CellPosition::new(x, y));
+                if let Some(cell) = cell
+                    && cell.symbol() == "█"
+                {
+                    cell.set_symbol(symbol);
+                }
+            }
+        }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         if frame.area().width != self.last_sizes.0 || frame.area().height != self.last_sizes.1 {
             (
                 self.owner_score_widget,
                 self.game_widget,
                 self.guest_score_widget,
+                self.hud_widget,
             ) = setup_widgets(frame);
             self.last_sizes = (frame.area().width, frame.area().height);
         }
 
-        self.render_game(frame);
+        self.render_game(frame, theme);
+
+        let (owner_account, guest_account) = match self.current_player {
+            CurrentPlayer::PlayerA => (self.account.as_deref(), None),
+            CurrentPlayer::PlayerB => (None, self.account.as_deref()),
+        };
 
         let owner_score = get_score_paragraph(
             "Player 1: ",
             &self.player_a.player.nickname,
             self.player_a.score,
+            owner_account,
+            theme,
         );
 
         frame.render_widget(owner_score, self.owner_score_widget);
@@ -188,10 +454,21 @@ impl Game {
             "Player 2: ",
             &self.player_b.player.nickname,
             self.player_b.score,
+            guest_account,
+            theme,
         );
 
         frame.render_widget(guest_score, self.guest_score_widget);
 
+        if self.show_hud {
+            frame.render_widget(self.get_hud_paragraph(theme), self.hud_widget);
+        }
+
+        if let Some(menu) = &self.menu {
+            menu.render(frame, self.game_widget, theme);
+        }
+
+        self.shown_timer = self.timer();
         self.needs_update = false;
     }
 
@@ -200,45 +477,152 @@ impl Game {
           return None;
         }
 
-        let direction = match (key, kind) {
+        let direction = match (self.keymap.action(key), kind) {
             (_, KeyEventKind::Release) => PaddleMoveDirection::None,
-            (KeyCode::Up, _) => PaddleMoveDirection::Up,
-            (KeyCode::Down, _) => PaddleMoveDirection::Down,
+            (Some(Action::Up), _) => PaddleMoveDirection::Up,
+            (Some(Action::Down), _) => PaddleMoveDirection::Down,
             _ => unreachable!(),
         };
 
+        self.mouse_target = None;
+        self.mouse_direction = PaddleMoveDirection::None;
+
         self.current_movement.update(&direction);
         if !self.current_movement.first_keystroke() {
             return None;
         }
 
-        let movements = match self.current_player {
-            CurrentPlayer::PlayerA => (direction.clone(), PaddleMoveDirection::None),
-            CurrentPlayer::PlayerB => (PaddleMoveDirection::None, direction.clone()),
-        };
+        Some(PageResults::UpdatePaddleMovement(self.movements(direction)))
+    }
+
+    pub fn mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        if self.is_starting || self.is_paused || self.menu.is_some() {
+            return None;
+        }
+
+        match event.kind {
+            MouseEventKind::Moved
+            | MouseEventKind::Down(MouseButton::Left)
+            | MouseEventKind::Drag(MouseButton::Left) => (),
+            _ => return None,
+        }
+
+        let field = self.game_widget.inner(Margin::new(1, 1));
+        if field.height == 0 || !field.contains(CellPosition::new(event.column, event.row)) {
+            return None;
+        }
+
+        let row = (event.row - field.y) as f64 + 0.5;
+        let pos_y = row / field.height as f64 * 600.0 - PADDLE_HEIGHT / 2.0;
+        self.mouse_target = Some(pos_y.clamp(0.0, 600.0 - PADDLE_HEIGHT));
 
-        Some(PageResults::UpdatePaddleMovement(movements))
+        None
     }
 
     pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
-        if let Event::Key(key) = event {
-            match key.code {
-                KeyCode::Esc => return Some(PageResults::BackToMenu),
-                KeyCode::Up | KeyCode::Down => return self.update_movement(key.code, key.kind),
-                KeyCode::Char('p') => {
-                  if key.kind == KeyEventKind::Repeat || key.kind == KeyEventKind::Release {
-                    return None;
-                  }
-                  return Some(PageResults::GamePaused(!self.is_paused));
-                },
-                _ => (),
+        let Event::Key(key) = event else {
+            return None;
+        };
+        let action = self.keymap.action(key.code);
+
+        if let Some(menu) = self.menu.as_mut() {
+            // Releases still have to stop a paddle that was moving when the menu opened
+            if key.kind == KeyEventKind::Release {
+                return match action {
+                    Some(Action::Up | Action::Down) => self.update_movement(key.code, key.kind),
+                    _ => None,
+                };
+            }
+
+            self.needs_update = true;
+            return match menu.key_event(key)? {
+                MenuAction::Resume => {
+                    self.menu = None;
+                    match std::mem::take(&mut self.paused_by_menu) {
+                        true => Some(PageResults::GamePaused(false)),
+                        false => None,
+                    }
+                }
+                MenuAction::Leave => Some(PageResults::BackToMenu),
+                MenuAction::ThemeSelected(name) => {
+                    self.theme = name.clone();
+                    Some(PageResults::ThemeSelected(name))
+                }
+                MenuAction::KeymapChanged(keymap) => {
+                    self.keymap = keymap;
+                    Some(PageResults::KeymapChanged(keymap))
+                }
+            };
+        }
+
+        match (key.code, action) {
+            (KeyCode::Esc, _) if key.kind == KeyEventKind::Press => {
+                self.menu = Some(PauseMenu::new(self.themes.clone(), &self.theme, self.keymap));
+                self.needs_update = true;
+                if !self.is_paused && !self.is_starting {
+                    self.paused_by_menu = true;
+                    self.stats.request_pause();
+                    return Some(PageResults::GamePaused(true));
+                }
+            }
+            (_, Some(Action::Up | Action::Down)) => return self.update_movement(key.code, key.kind),
+            (_, Some(Action::Pause)) if key.kind == KeyEventKind::Press => {
+                if !self.is_paused {
+                    self.stats.request_pause();
+                }
+                return Some(PageResults::GamePaused(!self.is_paused));
+            }
+            (_, Some(Action::Hud)) if key.kind == KeyEventKind::Press => {
+                self.show_hud = !self.show_hud;
+                self.needs_update = true;
             }
+            _ => (),
         }
         None
     }
 
+    fn get_hud_paragraph(&self, theme: &Theme) -> Paragraph<'_> {
+        let line = |label: &'static str, value: String| {
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.muted)),
+                Span::styled(value, Style::default().fg(theme.text)),
+            ])
+        };
+
+        let mut lines = Vec::new();
+        if self.hud.clock {
+            let elapsed = self.stats.elapsed().as_secs();
+            lines.push(line("Time: ", format!("{:02}:{:02}", elapsed / 60, elapsed % 60)));
+        }
+        if self.hud.rally {
+            lines.push(line("Rally: ", self.stats.rally().to_string()));
+        }
+        if self.hud.longest_rally {
+            lines.push(line("Longest rally: ", self.stats.longest_rally().to_string()));
+        }
+        if self.hud.ball_speed {
+            let speed = match self.stats.ball_speed() {
+                Some(speed) => format!("{:.0} px/s", speed),
+                None => "-".to_string(),
+            };
+            lines.push(line("Ball speed: ", speed));
+        }
+        if self.hud.paused_by
+            && let Some(paused_by) = self.stats.paused_by()
+        {
+            let nickname = match (paused_by, &self.current_player) {
+                (PausedBy::Me, _) => "you",
+                (PausedBy::Opponent, CurrentPlayer::PlayerA) => &self.player_b.player.nickname,
+                (PausedBy::Opponent, CurrentPlayer::PlayerB) => &self.player_a.player.nickname,
+            };
+            lines.push(line("Paused by: ", nickname.to_string()));
+        }
+
+        Paragraph::new(Text::from(lines)).centered()
+    }
+
     pub fn needs_update(&self) -> bool {
-        self.needs_update
+        self.needs_update || self.timer() != self.shown_timer
     }
 
     pub fn game_over(&self, game_over: &GameOverEvent) -> GameResult {
@@ -247,8 +631,12 @@ impl Game {
             CurrentPlayer::PlayerB => game_over.final_score.guest > game_over.final_score.owner,
         };
 
+        let mut stats = self.stats.clone();
+        stats.finish(game_over.final_score.owner, game_over.final_score.guest);
+
         GameResult {
             won,
+            is_owner: self.current_player == CurrentPlayer::PlayerA,
             player_a: GameResultPlayer {
                 player: self.player_a.player.clone(),
                 score: game_over.final_score.owner,
@@ -257,11 +645,12 @@ impl Game {
                 player: self.player_b.player.clone(),
                 score: game_over.final_score.guest,
             },
+            stats,
         }
     }
 }
 
-fn setup_widgets(frame: &Frame) -> (Rect, Rect, Rect) {
+fn setup_widgets(frame: &Frame) -> (Rect, Rect, Rect, Rect) {
     let [left, game_container, right] = Layout::horizontal([
         Constraint::Length(30),
         Constraint::Min(0),
@@ -269,15 +658,18 @@ fn setup_widgets(frame: &Frame) -> (Rect, Rect, Rect) {
     ])
     .areas(frame.area());
 
-    let game = get_game_area(game_container);
+    let cell_ratio = capabilities::cell_ratio().unwrap_or(FALLBACK_CELL_RATIO);
+    let game = get_game_area(game_container, cell_ratio);
 
-    let [_, owner, _] = Layout::vertical([
+    let [_, owner, below_owner] = Layout::vertical([
         Constraint::Min(0),
         Constraint::Length(3),
         Constraint::Min(0),
     ])
     .areas(left);
 
+    let hud = below_owner.inner(Margin::new(0, 1));
+
     let [_, guest, _] = Layout::vertical([
         Constraint::Min(0),
         Constraint::Length(3),
@@ -285,12 +677,11 @@ fn setup_widgets(frame: &Frame) -> (Rect, Rect, Rect) {
     ])
     .areas(right);
 
-    (owner, game, guest)
+    (owner, game, guest, hud)
 }
 
-fn get_game_area(area: Rect) -> Rect {
+fn get_game_area(area: Rect, termchar_ratio: f64) -> Rect {
     let game_aspect_ratio = 800.0 / 600.0;
-    let termchar_ratio = 1.9;
     let term_ratio = area.width as f64 / (area.height as f64 * termchar_ratio);
 
     let (width, height) = match term_ratio > game_aspect_ratio {
@@ -321,24 +712,109 @@ fn get_game_area(area: Rect) -> Rect {
     game
 }
 
-fn get_score_paragraph<'a>(title: &'a str, nickname: &'a str, score: usize) -> Paragraph<'a> {
-    let lines = vec![
+fn get_score_paragraph<'a>(
+    title: &'a str,
+    nickname: &'a str,
+    score: usize,
+    account: Option<&'a str>,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let mut lines = vec![
         Line::from(vec![
-            Span::styled(title, Style::default()),
-            Span::styled(
-                nickname,
-                Style::default().bold().fg(Color::Rgb(34, 211, 238)),
-            ),
+            Span::styled(title, Style::default().fg(theme.text)),
+            Span::styled(nickname, Style::default().bold().fg(theme.accent)),
         ]),
         Line::from(vec![
-            Span::styled("Score: ", Style::default()),
+            Span::styled("Score: ", Style::default().fg(theme.text)),
             Span::styled(
                 format!("{}", score),
-                Style::default().bold().fg(Color::Rgb(253, 224, 71)),
+                Style::default().bold().fg(theme.score),
             ),
         ]),
     ];
+    // Marks the side of the active account
+    if let Some(account) = account {
+        lines.push(Line::from(vec![
+            Span::styled("You: ", Style::default().fg(theme.muted)),
+            Span::styled(account, Style::default().fg(theme.status)),
+        ]));
+    }
 
     let text = Text::from(lines);
     Paragraph::new(text).centered()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [(u16, u16); 8] = [
+        (20, 10),
+        (80, 24),
+        (100, 30),
+        (120, 40),
+        (160, 50),
+        (200, 60),
+        (60, 80),
+        (300, 20),
+    ];
+    const RATIOS: [f64; 4] = [1.6, FALLBACK_CELL_RATIO, 2.0, 2.4];
+
+    #[test]
+    fn game_area_fits_inside_container() {
+        for (width, height) in SIZES {
+            for ratio in RATIOS {
+                let area = Rect::new(5, 3, width, height);
+                let game = get_game_area(area, ratio);
+
+                assert_eq!(area.intersection(game), game, "{width}x{height} @ {ratio}");
+                assert!(game.width == width || game.height == height);
+            }
+        }
+    }
+
+    #[test]
+    fn game_area_is_centered() {
+        for (width, height) in SIZES {
+            for ratio in RATIOS {
+                let area = Rect::new(0, 0, width, height);
+                let game = get_game_area(area, ratio);
+
+                let left = game.left() - area.left();
+                let right = area.right() - game.right();
+                let top = game.top() - area.top();
+                let bottom = area.bottom() - game.bottom();
+                assert!(left.abs_diff(right) <= 1, "{width}x{height} @ {ratio}");
+                assert!(top.abs_diff(bottom) <= 1, "{width}x{height} @ {ratio}");
+            }
+        }
+    }
+
+    #[test]
+    fn game_area_keeps_aspect_ratio_in_pixels() {
+        for (width, height) in SIZES {
+            for ratio in RATIOS {
+                let game = get_game_area(Rect::new(0, 0, width, height), ratio);
+                let pixel_ratio = game.width as f64 / (game.height as f64 * ratio);
+
+                // Rounding to whole cells costs at most one row or column
+                let min = (game.width as f64 - 1.0) / ((game.height as f64 + 1.0) * ratio);
+                let max = (game.width as f64 + 1.0) / ((game.height as f64 - 1.0) * ratio);
+                assert!(
+                    min <= 4.0 / 3.0 && 4.0 / 3.0 <= max,
+                    "{width}x{height} @ {ratio}: {pixel_ratio}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn taller_cells_need_more_columns() {
+        let area = Rect::new(0, 0, 200, 50);
+        let tall = get_game_area(area, 2.4);
+        let square = get_game_area(area, 1.6);
+
+        assert_eq!(tall.height, square.height);
+        assert!(tall.width > square.width);
+    }
+}
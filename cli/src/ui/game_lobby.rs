@@ -2,11 +2,11 @@ use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    style::Style,
+    widgets::Paragraph,
 };
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
 #[derive(Debug, Clone)]
 pub struct GameLobbyPage {
@@ -21,7 +21,7 @@ impl GameLobbyPage {
             needs_update: true,
         }
     }
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -32,21 +32,21 @@ impl GameLobbyPage {
         let [_, room_id] =
             Layout::vertical([Constraint::Percentage(30), Constraint::Length(3)]).areas(horizontal);
 
-        let style: Style = Color::Gray.into();
+        let style: Style = theme.muted.into();
 
         let input = Paragraph::new(format!("Room ID: {}", self.room_id))
             .style(style)
-            .block(Block::bordered());
+            .block(theme.block());
         frame.render_widget(input, room_id);
 
         self.needs_update = false;
     }
 
     pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
-        if let Event::Key(key) = event {
-            if key.code == KeyCode::Esc {
-                return Some(PageResults::BackToMenu);
-            }
+        if let Event::Key(key) = event
+            && key.code == KeyCode::Esc
+        {
+            return Some(PageResults::BackToMenu);
         }
         None
     }
@@ -1,16 +1,41 @@
+use std::time::Duration;
+
 use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout},
-    widgets::{Block, Paragraph},
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Paragraph, Sparkline},
 };
 
-use super::{game::GameResult, pages::PageResults};
+use crate::websocket::events::websocketevents::GameStartEventPlayer;
+
+use super::{game::GameResult, pages::PageResults, theme::Theme};
+
+const STATS_HEIGHT: u16 = 5;
+const SPARKLINE_HEIGHT: u16 = 5;
+const RALLIES_HEIGHT: u16 = 10;
+// Result, rematch hint and statistics, which are always shown
+const SUMMARY_HEIGHT: u16 = 4 + 3 + STATS_HEIGHT;
+
+#[derive(Debug, Clone)]
+enum RematchState {
+    Idle,
+    Waiting(String),
+    Failed(String),
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
 
 #[derive(Debug, Clone)]
 pub struct GameOverPage {
     needs_update: bool,
     game_result: GameResult,
+    rematch: RematchState,
 }
 
 impl GameOverPage {
@@ -18,19 +43,63 @@ impl GameOverPage {
         Self {
             needs_update: true,
             game_result,
+            rematch: RematchState::Idle,
+        }
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.game_result.is_owner
+    }
+
+    pub fn opponent(&self) -> &GameStartEventPlayer {
+        match self.game_result.is_owner {
+            true => &self.game_result.player_b.player,
+            false => &self.game_result.player_a.player,
         }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn is_waiting_for_rematch(&self) -> bool {
+        matches!(self.rematch, RematchState::Waiting(_))
+    }
+
+    pub fn rematch_waiting(&mut self, message: String) {
+        self.rematch = RematchState::Waiting(message);
+        self.needs_update = true;
+    }
+
+    pub fn rematch_error(&mut self, message: String) {
+        self.rematch = RematchState::Failed(message);
+        self.needs_update = true;
+    }
+
+    pub fn rematch_cancelled(&mut self) {
+        self.rematch = RematchState::Idle;
+        self.needs_update = true;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
-            Constraint::Length(42),
+            Constraint::Length(60),
             Constraint::Min(0),
         ])
         .areas(frame.area());
 
-        let [_, area] =
-            Layout::vertical([Constraint::Percentage(30), Constraint::Length(4)]).areas(horizontal);
+        // The charts are dropped, least useful first, when the terminal is too short
+        let height = frame.area().height;
+        let show_rallies = height >= SUMMARY_HEIGHT + RALLIES_HEIGHT;
+        let show_sparkline = height >= SUMMARY_HEIGHT + RALLIES_HEIGHT + SPARKLINE_HEIGHT;
+
+        let [_, area, rematch_area, stats_area, sparkline_area, rallies_area, _] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(4),
+            Constraint::Length(3),
+            Constraint::Length(STATS_HEIGHT),
+            Constraint::Length(if show_sparkline { SPARKLINE_HEIGHT } else { 0 }),
+            Constraint::Length(if show_rallies { RALLIES_HEIGHT } else { 0 }),
+            Constraint::Min(0),
+        ])
+        .areas(horizontal);
 
         let title = format!("You {}", if self.game_result.won { "won" } else { "lost" });
         let content = format!(
@@ -41,16 +110,143 @@ impl GameOverPage {
             self.game_result.player_b.score
         );
 
-        let input = Paragraph::new(content).block(Block::bordered().title(title));
+        let input = Paragraph::new(content)
+            .style(theme.text)
+            .block(theme.block().title(title));
         frame.render_widget(input, area);
 
+        let rematch = match &self.rematch {
+            RematchState::Idle => Paragraph::new("r: rematch, Enter: back to menu").style(theme.muted),
+            RematchState::Waiting(message) => {
+                Paragraph::new(format!("{} (Esc to cancel)", message)).style(theme.status)
+            }
+            RematchState::Failed(message) => Paragraph::new(message.as_str()).style(theme.error),
+        };
+        frame.render_widget(rematch.block(theme.block().title("Rematch")), rematch_area);
+
+        self.render_stats(frame, theme, stats_area);
+        if show_sparkline {
+            self.render_score_differential(frame, theme, sparkline_area);
+        }
+        if show_rallies {
+            self.render_rallies(frame, theme, rallies_area);
+        }
+
         self.needs_update = false;
     }
 
+    fn render_stats(&self, frame: &mut Frame, theme: &Theme, area: Rect) {
+        let stats = &self.game_result.stats;
+        let points = stats.points();
+
+        let longest_point = points
+            .iter()
+            .scan(Duration::ZERO, |previous, point| {
+                let length = point.at.saturating_sub(*previous);
+                *previous = point.at;
+                Some(length)
+            })
+            .max()
+            .unwrap_or_default();
+        let average_rally = match points.len() {
+            0 => 0.0,
+            len => points.iter().map(|point| point.rally).sum::<usize>() as f64 / len as f64,
+        };
+        let average_speed = match stats.average_speed() {
+            Some(speed) => format!("{:.0} px/s", speed),
+            None => "-".to_string(),
+        };
+
+        let line = |label: &'static str, value: String| {
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.muted)),
+                Span::styled(value, Style::default().fg(theme.text)),
+            ])
+        };
+
+        let left = vec![
+            line("Match time: ", format_duration(stats.elapsed())),
+            line("Longest point: ", format_duration(longest_point)),
+            line("Time paused: ", format_duration(stats.paused_time())),
+        ];
+        let right = vec![
+            line("Rallies per point: ", format!("{:.1}", average_rally)),
+            line("Longest rally: ", stats.longest_rally().to_string()),
+            line("Average ball speed: ", average_speed),
+        ];
+
+        let block = theme.block().title("Statistics");
+        let [left_area, right_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(block.inner(area));
+        frame.render_widget(block, area);
+        frame.render_widget(Paragraph::new(left), left_area);
+        frame.render_widget(Paragraph::new(right), right_area);
+    }
+
+    /// Score difference from this player's point of view after every point,
+    /// shifted so the lowest value sits on the baseline.
+    fn render_score_differential(&self, frame: &mut Frame, theme: &Theme, area: Rect) {
+        let is_owner = self.game_result.is_owner;
+        let differential: Vec<i64> = std::iter::once(0)
+            .chain(self.game_result.stats.points().iter().scan(0, |diff, point| {
+                *diff += match point.owner_scored == is_owner {
+                    true => 1,
+                    false => -1,
+                };
+                Some(*diff)
+            }))
+            .collect();
+
+        let min = differential.iter().copied().min().unwrap_or(0);
+        let max = differential.iter().copied().max().unwrap_or(0);
+        let data: Vec<u64> = differential.iter().map(|diff| (diff - min) as u64).collect();
+
+        let title = format!("Score differential ({:+} to {:+})", min, max);
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .max((max - min).max(1) as u64)
+            .style(theme.accent)
+            .block(theme.block().title(title));
+        frame.render_widget(sparkline, area);
+    }
+
+    fn render_rallies(&self, frame: &mut Frame, theme: &Theme, area: Rect) {
+        let bars: Vec<Bar> = self
+            .game_result
+            .stats
+            .points()
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let color = match point.owner_scored {
+                    true => theme.paddle_a,
+                    false => theme.paddle_b,
+                };
+                Bar::default()
+                    .value(point.rally as u64)
+                    .label(Line::from((i + 1).to_string()))
+                    .style(color)
+                    .value_style(Style::default().fg(theme.text).bg(color))
+            })
+            .collect();
+
+        let chart = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .label_style(theme.muted)
+            .block(theme.block().title("Rallies per point"));
+        frame.render_widget(chart, area);
+    }
+
     pub fn key_event(&self, event: &Event) -> Option<PageResults> {
         if let Event::Key(key) = event {
-            match key.code {
-                KeyCode::Esc | KeyCode::Enter => return Some(PageResults::GameOver),
+            match (&self.rematch, key.code) {
+                (RematchState::Waiting(_), KeyCode::Esc) => return Some(PageResults::CancelRematch),
+                (RematchState::Waiting(_), _) => (),
+                (_, KeyCode::Char('r')) => return Some(PageResults::Rematch),
+                (_, KeyCode::Esc | KeyCode::Enter) => return Some(PageResults::GameOver),
                 _ => (),
             }
         }
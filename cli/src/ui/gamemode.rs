@@ -1,61 +1,86 @@
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
 };
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GameModes {
     SinglePlayer,
     CreateRoom,
     JoinRoom,
+    Account,
+    Security,
+    Settings,
+    SwitchAccount,
+    ForgetSession,
+    Logout,
 }
 
+const OPTIONS: [(GameModes, &str); 9] = [
+    (GameModes::SinglePlayer, "Single Player"),
+    (GameModes::CreateRoom, "Create Room"),
+    (GameModes::JoinRoom, "Join Room"),
+    (GameModes::Account, "Account"),
+    (GameModes::Security, "Security"),
+    (GameModes::Settings, "Settings"),
+    (GameModes::SwitchAccount, "Switch Account"),
+    (GameModes::ForgetSession, "Forget Session"),
+    (GameModes::Logout, "Logout"),
+];
+
 impl GameModes {
+    fn index(&self) -> usize {
+        OPTIONS.iter().position(|(mode, _)| mode == self).unwrap_or_default()
+    }
+
     fn toggle(&self) -> Self {
-        match self {
-            Self::SinglePlayer => Self::CreateRoom,
-            Self::CreateRoom => Self::JoinRoom,
-            Self::JoinRoom => Self::SinglePlayer,
-        }
+        OPTIONS[(self.index() + 1) % OPTIONS.len()].0.clone()
     }
 
     fn prev(&self) -> Self {
-        match self {
-            Self::JoinRoom => Self::CreateRoom,
-            _ => Self::SinglePlayer,
-        }
+        OPTIONS[self.index().saturating_sub(1)].0.clone()
     }
 
     fn next(&self) -> Self {
-        match self {
-            Self::SinglePlayer => Self::CreateRoom,
-            _ => Self::JoinRoom,
-        }
+        OPTIONS[(self.index() + 1).min(OPTIONS.len() - 1)].0.clone()
     }
 }
 
+/// Rows of the account line, the options and the message box.
+fn menu_height(row_height: u16) -> u16 {
+    1 + row_height * OPTIONS.len() as u16 + 3
+}
+
 #[derive(Debug, Clone)]
 pub struct GameModePage {
     selection: GameModes,
+    /// Nickname of the account in use.
+    account: Option<String>,
+    option_areas: Vec<(GameModes, Rect)>,
     needs_update: bool,
     error_message: Option<String>,
+    info_message: Option<String>,
 }
 
 impl GameModePage {
-    pub fn new() -> Self {
+    pub fn new(account: Option<String>) -> Self {
         Self {
             selection: GameModes::SinglePlayer,
+            account,
+            option_areas: Vec::new(),
             needs_update: true,
             error_message: None,
+            info_message: None,
         }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -63,53 +88,60 @@ impl GameModePage {
         ])
         .areas(frame.area());
 
-        let [_, single, createroom, joinroom, error] = Layout::vertical([
-            Constraint::Percentage(30),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
+        // Bordered options need 31 rows, short terminals get one row per option
+        let height = frame.area().height;
+        let row_height = match height - height * 3 / 10 >= menu_height(3) {
+            true => 3,
+            false => 1,
+        };
+        let top = (height * 3 / 10).min(height.saturating_sub(menu_height(row_height)));
+
+        let [_, account, options, error] = Layout::vertical([
+            Constraint::Length(top),
+            Constraint::Length(1),
+            Constraint::Length(row_height * OPTIONS.len() as u16),
             Constraint::Length(3),
         ])
         .areas(horizontal);
-
-        let style: Style = match self.selection {
-            GameModes::SinglePlayer => Color::Rgb(255, 0, 255),
-            _ => Color::Gray,
+        let areas = Layout::vertical([Constraint::Length(row_height); OPTIONS.len()]).split(options);
+
+        if let Some(nickname) = &self.account {
+            let line = Line::from(vec![
+                Span::styled("Playing as ", theme.muted),
+                Span::styled(nickname.clone(), theme.accent),
+            ]);
+            frame.render_widget(Paragraph::new(line).centered(), account);
         }
-        .into();
-
-        let input = Paragraph::new("Single Player")
-            .style(style)
-            .block(Block::bordered());
-        frame.render_widget(input, single);
-
-        let style: Style = match self.selection {
-            GameModes::CreateRoom => Color::Rgb(255, 0, 255),
-            _ => Color::Gray,
-        }
-        .into();
-
-        let input = Paragraph::new("Create Room")
-            .style(style)
-            .block(Block::bordered());
-        frame.render_widget(input, createroom);
 
-        let style: Style = match self.selection {
-            GameModes::JoinRoom => Color::Rgb(255, 0, 255),
-            _ => Color::Gray,
+        self.option_areas.clear();
+        for ((mode, label), area) in OPTIONS.iter().zip(areas.iter()) {
+            let style = theme.selection(self.selection.eq(mode));
+
+            let input = match row_height {
+                // Without the border the selection needs more than a color
+                1 if self.selection.eq(mode) => Paragraph::new(*label)
+                    .style(style.add_modifier(Modifier::REVERSED))
+                    .centered(),
+                1 => Paragraph::new(*label).style(style).centered(),
+                _ => Paragraph::new(*label)
+                    .style(style)
+                    .block(theme.block().border_style(style)),
+            };
+            frame.render_widget(input, *area);
+            self.option_areas.push((mode.clone(), *area));
         }
-        .into();
-
-        let input = Paragraph::new("Join Room")
-            .style(style)
-            .block(Block::bordered());
-        frame.render_widget(input, joinroom);
 
         if let Some(msg) = self.error_message.as_ref() {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
+            frame.render_widget(input, error);
+        } else if let Some(msg) = self.info_message.as_ref() {
+            let style: Style = theme.text.into();
+            let input = Paragraph::new(msg.to_owned())
+                .style(style)
+                .block(theme.block().title("Info"));
             frame.render_widget(input, error);
         }
 
@@ -123,23 +155,51 @@ impl GameModePage {
                 KeyCode::Tab => self.focus_widget(self.selection.toggle()),
                 KeyCode::Up => self.focus_widget(self.selection.prev()),
                 KeyCode::Down => self.focus_widget(self.selection.next()),
-                KeyCode::Enter => match self.selection {
-                    GameModes::SinglePlayer => {
-                        return Some(PageResults::GameModeChosen(GameModes::SinglePlayer));
-                    }
-                    GameModes::CreateRoom => {
-                        return Some(PageResults::GameModeChosen(GameModes::CreateRoom));
-                    }
-                    GameModes::JoinRoom => {
-                        return Some(PageResults::GameModeChosen(GameModes::JoinRoom));
-                    }
-                },
+                KeyCode::Enter => return Some(Self::choose(self.selection.clone())),
                 _ => (),
             }
         }
         None
     }
 
+    pub fn mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        let position = Position::new(event.column, event.row);
+        let (mode, _) = self
+            .option_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))?;
+        let mode = mode.clone();
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => Some(Self::choose(mode)),
+            MouseEventKind::Moved if self.selection.ne(&mode) => {
+                self.focus_widget(mode);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn choose(mode: GameModes) -> PageResults {
+        match mode {
+            GameModes::ForgetSession => PageResults::ForgetSession,
+            GameModes::Logout => PageResults::Logout,
+            mode => PageResults::GameModeChosen(mode),
+        }
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
     pub fn needs_update(&self) -> bool {
         self.needs_update
     }
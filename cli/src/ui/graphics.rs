@@ -0,0 +1,206 @@
+use std::io::Write;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use flate2::{Compression, write::ZlibEncoder};
+use ratatui::{layout::Rect, style::Color};
+
+use crate::capabilities::{self, GraphicsProtocol};
+
+const FIELD_WIDTH: f64 = 800.0;
+const FIELD_HEIGHT: f64 = 600.0;
+// Without a known cell size kitty scales the image into the placement
+const KITTY_FALLBACK_SIZE: (usize, usize) = (400, 300);
+const KITTY_IMAGE_ID: u32 = 1;
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Indexed image of the game field. Index 0 is the background, which kitty
+/// draws transparent and sixel as black.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    palette: Vec<(u8, u8, u8)>,
+}
+
+impl Raster {
+    /// Picks a resolution for `area`, `None` if the protocol can't be used there.
+    pub fn for_area(protocol: GraphicsProtocol, area: Rect) -> Option<Self> {
+        let pixel_size = capabilities::cell_size().map(|(cell_width, cell_height)| {
+            let width = (area.width as f64 * cell_width).min(FIELD_WIDTH);
+            let height = (area.height as f64 * cell_height).min(FIELD_HEIGHT);
+            (width as usize, height as usize)
+        });
+
+        let (width, height) = match (protocol, pixel_size) {
+            (_, Some((0, _)) | Some((_, 0))) => return None,
+            (_, Some(size)) => size,
+            (GraphicsProtocol::Kitty, None) => KITTY_FALLBACK_SIZE,
+            // Sixel images are not scaled by the terminal
+            (GraphicsProtocol::Sixel, None) => return None,
+        };
+
+        Some(Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+            palette: vec![(0, 0, 0)],
+        })
+    }
+
+    pub fn add_color(&mut self, color: Color) -> u8 {
+        let rgb = capabilities::to_rgb(color).unwrap_or((255, 255, 255));
+        match self.palette.iter().skip(1).position(|entry| *entry == rgb) {
+            Some(index) => index as u8 + 1,
+            None => {
+                self.palette.push(rgb);
+                (self.palette.len() - 1) as u8
+            }
+        }
+    }
+
+    /// Fills a rectangle given in field coordinates (800x600, y pointing down).
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: u8) {
+        let (x0, y0) = self.to_pixel(x, y);
+        let (x1, y1) = self.to_pixel(x + width, y + height);
+
+        for py in y0..y1 {
+            self.pixels[py * self.width + x0..py * self.width + x1].fill(color);
+        }
+    }
+
+    pub fn fill_circle(&mut self, x: f64, y: f64, radius: f64, color: u8) {
+        let (x0, y0) = self.to_pixel(x - radius, y - radius);
+        let (x1, y1) = self.to_pixel(x + radius, y + radius);
+        let scale_x = FIELD_WIDTH / self.width as f64;
+        let scale_y = FIELD_HEIGHT / self.height as f64;
+
+        for py in y0..y1 {
+            let dy = (py as f64 + 0.5) * scale_y - y;
+            for px in x0..x1 {
+                let dx = (px as f64 + 0.5) * scale_x - x;
+                if dx * dx + dy * dy <= radius * radius {
+                    self.pixels[py * self.width + px] = color;
+                }
+            }
+        }
+    }
+
+    fn to_pixel(&self, x: f64, y: f64) -> (usize, usize) {
+        let px = (x / FIELD_WIDTH * self.width as f64).round();
+        let py = (y / FIELD_HEIGHT * self.height as f64).round();
+        (
+            px.clamp(0.0, self.width as f64) as usize,
+            py.clamp(0.0, self.height as f64) as usize,
+        )
+    }
+
+    /// Escape sequence that draws the image over `area`, leaving the cursor where it was.
+    pub fn encode(&self, protocol: GraphicsProtocol, area: Rect) -> Vec<u8> {
+        let mut out = Vec::new();
+        write!(out, "\x1b7\x1b[{};{}H", area.y + 1, area.x + 1).ok();
+        match protocol {
+            GraphicsProtocol::Kitty => self.encode_kitty(&mut out, area),
+            GraphicsProtocol::Sixel => self.encode_sixel(&mut out),
+        }
+        out.extend_from_slice(b"\x1b8");
+        out
+    }
+
+    fn encode_kitty(&self, out: &mut Vec<u8>, area: Rect) {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for &index in &self.pixels {
+            let (r, g, b) = self.palette[index as usize];
+            let alpha = if index == 0 { 0 } else { 255 };
+            rgba.extend_from_slice(&[r, g, b, alpha]);
+        }
+
+        // Most of the field is background, so the RGBA data shrinks a lot
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        let compressed = encoder.write_all(&rgba).and_then(|_| encoder.finish());
+        let (payload, compression) = match compressed {
+            Ok(data) => (STANDARD.encode(data), ",o=z"),
+            Err(_) => (STANDARD.encode(rgba), ""),
+        };
+        let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = (i + 1 < chunks.len()) as u8;
+            match i {
+                0 => write!(
+                    out,
+                    "\x1b_Ga=T,f=32{},s={},v={},c={},r={},i={},q=2,C=1,m={};",
+                    compression,
+                    self.width,
+                    self.height,
+                    area.width,
+                    area.height,
+                    KITTY_IMAGE_ID,
+                    more
+                ),
+                _ => write!(out, "\x1b_Gm={};", more),
+            }
+            .ok();
+            out.extend_from_slice(chunk);
+            out.extend_from_slice(b"\x1b\\");
+        }
+    }
+
+    fn encode_sixel(&self, out: &mut Vec<u8>) {
+        write!(out, "\x1bP0;0;0q\"1;1;{};{}", self.width, self.height).ok();
+        for (index, (r, g, b)) in self.palette.iter().enumerate() {
+            let percent = |value: u8| value as u32 * 100 / 255;
+            write!(out, "#{};2;{};{};{}", index, percent(*r), percent(*g), percent(*b)).ok();
+        }
+
+        for band in (0..self.height).step_by(6) {
+            let rows = (self.height - band).min(6);
+            for color in 0..self.palette.len() as u8 {
+                let sixels: Vec<u8> = (0..self.width)
+                    .map(|x| {
+                        (0..rows).fold(0, |bits, row| {
+                            match self.pixels[(band + row) * self.width + x] == color {
+                                true => bits | 1 << row,
+                                false => bits,
+                            }
+                        })
+                    })
+                    .collect();
+
+                if sixels.iter().all(|bits| *bits == 0) {
+                    continue;
+                }
+
+                write!(out, "#{}", color).ok();
+                write_sixel_runs(out, &sixels);
+                out.push(b'$');
+            }
+            out.push(b'-');
+        }
+        out.extend_from_slice(b"\x1b\\");
+    }
+}
+
+fn write_sixel_runs(out: &mut Vec<u8>, sixels: &[u8]) {
+    let mut i = 0;
+    while i < sixels.len() {
+        let run = sixels[i..].iter().take_while(|bits| **bits == sixels[i]).count();
+        let symbol = b'?' + sixels[i];
+        match run {
+            1..=3 => out.extend(std::iter::repeat_n(symbol, run)),
+            _ => {
+                write!(out, "!{}", run).ok();
+                out.push(symbol);
+            }
+        }
+        i += run;
+    }
+}
+
+/// Escape sequence that removes what the pixel renderer left on screen.
+pub fn clear_sequence(protocol: GraphicsProtocol) -> &'static [u8] {
+    match protocol {
+        GraphicsProtocol::Kitty => b"\x1b_Ga=d,d=A,q=2\x1b\\",
+        // Sixel pixels are gone once ratatui repaints the cells
+        GraphicsProtocol::Sixel => b"",
+    }
+}
@@ -1,187 +1,480 @@
-use crossterm::event::{Event, KeyCode};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    layout::{Constraint, Flex, Layout, Position, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
 };
 use tui_input::{Input, backend::crossterm::EventHandler};
 
-use crate::auth::LoginErrors;
+use crate::{
+    auth::LoginErrors,
+    hosts::{Health, HostEntry},
+    server::Server,
+};
+
+use super::{
+    pages::{LoginType, PageResults},
+    theme::Theme,
+};
 
-use super::pages::{LoginType, PageResults};
+/// Long enough for a full URL with port and path prefix.
+const HOST_MAX_LENGTH: usize = 255;
+const NAME_MAX_LENGTH: usize = 32;
+/// Rows of the host list, more hosts scroll.
+const LIST_ROWS: usize = 6;
+const HEALTH_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Debug, PartialEq, Clone)]
 enum Field {
+    Hosts,
     LocalLogin,
     RemoteLogin,
+    Signup,
+}
+
+/// Changes to the saved hosts, made by the app since it owns the store.
+#[derive(Debug)]
+pub enum HostRequest {
+    ToggleBookmark(String),
+    Rename(String, Option<String>),
+    Remove(String),
 }
 
 #[derive(Debug, Clone)]
 pub struct HostSelectorPage {
     host: Input,
     selected_field: Field,
+    option_areas: Vec<(Field, Rect)>,
+    hosts: Vec<HostEntry>,
+    host_index: usize,
+    hosts_area: Rect,
+    /// Display name being edited for the selected host.
+    renaming: Option<Input>,
+    health: HashMap<String, (Health, Instant)>,
     needs_update: bool,
     error_message: Option<String>,
-}
-
-#[derive(Debug)]
-pub enum LoginResult {
-    Login((String, String)),
-    Abort,
+    info_message: Option<String>,
 }
 
 impl HostSelectorPage {
-    pub fn new() -> Self {
+    /// Starts with the first saved host, or the default one without any.
+    pub fn new(hosts: Vec<HostEntry>) -> Self {
         let default_host = if cfg!(debug_assertions) {
-            "localhost"
+            "localhost:3000"
         } else {
             "ft-transcendence.at"
         };
+        let host = hosts.first().map(|host| host.url.clone()).unwrap_or(default_host.to_string());
 
         Self {
-            host: Input::default().with_value(default_host.to_string()),
+            host: Input::default().with_value(host),
             selected_field: Field::LocalLogin,
+            option_areas: Vec::new(),
+            hosts,
+            host_index: 0,
+            hosts_area: Rect::default(),
+            renaming: None,
+            health: HashMap::new(),
             needs_update: true,
             error_message: None,
+            info_message: None,
         }
     }
 
+    pub fn with_host(host: &str, hosts: Vec<HostEntry>) -> Self {
+        let mut page = Self::new(hosts);
+        page.host = Input::default().with_value(host.to_string());
+        page.host_index = page.hosts.iter().position(|entry| entry.url == host).unwrap_or_default();
+        page
+    }
+
+    pub fn set_hosts(&mut self, hosts: Vec<HostEntry>) {
+        let selected = self.hosts.get(self.host_index).map(|host| host.url.clone());
+        self.hosts = hosts;
+        self.host_index = selected
+            .and_then(|url| self.hosts.iter().position(|host| host.url == url))
+            .unwrap_or_default()
+            .min(self.hosts.len().saturating_sub(1));
+        if self.hosts.is_empty() && self.selected_field == Field::Hosts {
+            self.selected_field = Field::LocalLogin;
+        }
+        self.needs_update = true;
+    }
+
+    /// Hosts whose health is unknown or outdated, they count as being checked
+    /// from now on.
+    pub fn stale_health(&mut self) -> Vec<String> {
+        let stale: Vec<String> = self
+            .hosts
+            .iter()
+            .filter(|host| match self.health.get(&host.url) {
+                Some((Health::Checking, _)) => false,
+                Some((_, checked)) => checked.elapsed() > HEALTH_INTERVAL,
+                None => true,
+            })
+            .map(|host| host.url.clone())
+            .collect();
+
+        for url in &stale {
+            self.health.insert(url.clone(), (Health::Checking, Instant::now()));
+        }
+        stale
+    }
+
+    pub fn set_health(&mut self, url: String, health: Health) {
+        self.health.insert(url, (health, Instant::now()));
+        self.needs_update = true;
+    }
+
     fn render_input_field(
         &self,
         frame: &mut Frame,
+        theme: &Theme,
         rect: Rect,
         field: &Input,
         title: &str,
         focused: bool,
     ) {
-        let style: Style = Color::Yellow.into();
+        let style: Style = theme.input.into();
+        let width = rect.width.saturating_sub(2) as usize;
+        let scroll = field.visual_scroll(width.saturating_sub(1));
 
         let text = field.value().to_owned();
 
         let input = Paragraph::new(text)
             .style(style)
-            .block(Block::bordered().title(title));
+            .scroll((0, scroll as u16))
+            .block(theme.block().title(title));
 
         frame.render_widget(input, rect);
 
         if focused {
-            let x = field.value().len() + 1;
+            let x = field.visual_cursor().saturating_sub(scroll) + 1;
             frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
         }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    fn render_hosts(&mut self, frame: &mut Frame, theme: &Theme, rect: Rect) {
+        self.hosts_area = rect;
+        let focused = self.selected_field == Field::Hosts;
+        let offset = self.host_index.saturating_sub(LIST_ROWS - 1);
+        let label_width = (rect.width as usize).saturating_sub(2 + 2 + 7 + 10);
+
+        let lines: Vec<Line> = self
+            .hosts
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(LIST_ROWS)
+            .map(|(index, host)| {
+                let style = match focused && index == self.host_index {
+                    true => theme.selection(true),
+                    false => Style::from(theme.text),
+                };
+                let (health, health_style) = match self.health.get(&host.url) {
+                    Some((Health::Up(latency), _)) => (format!("up {} ms", latency.as_millis()), theme.accent),
+                    Some((Health::Failing(status), _)) => (format!("error {}", status), theme.error),
+                    Some((Health::Down, _)) => ("down".to_string(), theme.error),
+                    Some((Health::Checking, _)) | None => ("...".to_string(), theme.muted),
+                };
+                let mark = match host.bookmarked {
+                    true => "* ",
+                    false => "  ",
+                };
+                let label: String = host.label().chars().take(label_width).collect();
+                Line::from(vec![
+                    Span::styled(format!("{}{:<width$}", mark, label, width = label_width), style),
+                    Span::styled(format!(" {:<6}", host.login.label()), theme.muted),
+                    Span::styled(format!("{:>10}", health), health_style),
+                ])
+            })
+            .collect();
+
+        let hint = match focused {
+            true => "Enter: connect  b: bookmark  r: rename  Del: remove",
+            false => "Up: pick a saved host",
+        };
+        let widget = Paragraph::new(lines).block(theme.block().title("Hosts").title_bottom(hint));
+        frame.render_widget(widget, rect);
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let list_height = match self.hosts.len() {
+            0 => 0,
+            len => len.min(LIST_ROWS) as u16 + 2,
+        };
+        let width = match self.hosts.is_empty() {
+            true => 42,
+            false => 64,
+        };
+
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
-            Constraint::Length(42),
+            Constraint::Length(width),
             Constraint::Min(0),
         ])
         .areas(frame.area());
 
-        let [_, host, local_login, remote_login, error] = Layout::vertical([
-            Constraint::Percentage(30),
+        let [hosts, host, local_login, remote_login, signup, error] = Layout::vertical([
+            Constraint::Length(list_height),
+            Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
         ])
+        .flex(Flex::Center)
         .areas(horizontal);
 
-        self.render_input_field(
-            frame,
-            host,
-            &self.host,
-            "Hostname",
-            true
-            // self.selected_field.eq(&Field::Host),
-        );
-
-        let color = match self.selected_field.eq(&Field::LocalLogin) {
-          true => Color::Rgb(255, 0, 255),
-          false => Color::Gray,
-        };
-        let style: Style = color.into();
+        self.option_areas = vec![
+            (Field::LocalLogin, local_login),
+            (Field::RemoteLogin, remote_login),
+            (Field::Signup, signup),
+        ];
+
+        if !self.hosts.is_empty() {
+            self.render_hosts(frame, theme, hosts);
+        }
+
+        match &self.renaming {
+            Some(name) => self.render_input_field(frame, theme, host, name, "Display name (Enter: save, Esc: cancel)", true),
+            None => self.render_input_field(frame, theme, host, &self.host, "Hostname", true),
+        }
+
+        let style = theme.selection(self.selected_field.eq(&Field::LocalLogin));
 
         let local = Paragraph::new("Local Login".to_owned())
           .style(style)
-          .block(Block::bordered());
+          .block(theme.block().border_style(style));
 
         frame.render_widget(local, local_login);
 
-        let color = match self.selected_field.eq(&Field::RemoteLogin) {
-          true => Color::Rgb(255, 0, 255),
-          false => Color::Gray,
-        };
-        let style: Style = color.into();
+        let style = theme.selection(self.selected_field.eq(&Field::RemoteLogin));
 
         let remote = Paragraph::new("Remote Login through 42".to_owned())
           .style(style)
-          .block(Block::bordered());
+          .block(theme.block().border_style(style));
 
         frame.render_widget(remote, remote_login);
 
+        let style = theme.selection(self.selected_field.eq(&Field::Signup));
+
+        let create = Paragraph::new("Create Account".to_owned())
+          .style(style)
+          .block(theme.block().border_style(style));
+
+        frame.render_widget(create, signup);
+
         if let Some(msg) = &self.error_message {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
+            frame.render_widget(input, error);
+        } else if let Some(msg) = &self.info_message {
+            let style: Style = theme.text.into();
+            let input = Paragraph::new(msg.to_owned())
+                .style(style)
+                .block(theme.block().title("Info"));
             frame.render_widget(input, error);
         }
         self.needs_update = false;
     }
 
     pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
-        if let Event::Key(key) = event {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if self.renaming.is_some() {
+            return self.rename_key_event(event, key.code);
+        }
+        if self.selected_field == Field::Hosts
+            && let Some(url) = self.hosts.get(self.host_index).map(|host| host.url.clone())
+        {
             match key.code {
-                KeyCode::Esc => return Some(PageResults::Exit),
-                KeyCode::Char(c) => {
-                    if self.host.value().len() < 32 && c.is_ascii_graphic() {
-                          self.host.handle_event(event);
-                          self.needs_update = true;
-                    }
-                },
-                KeyCode::Backspace => {
-                    self.host.handle_event(event);
+                KeyCode::Char('b') => return Some(PageResults::Host(HostRequest::ToggleBookmark(url))),
+                KeyCode::Delete => return Some(PageResults::Host(HostRequest::Remove(url))),
+                KeyCode::Char('r') => {
+                    let name = self.hosts[self.host_index].name.clone().unwrap_or_default();
+                    self.renaming = Some(Input::new(name));
                     self.needs_update = true;
+                    return None;
+                }
+                _ => (),
+            }
+        }
+
+        match key.code {
+            KeyCode::Esc => return Some(PageResults::Exit),
+            KeyCode::Char(c) if self.host.value().len() < HOST_MAX_LENGTH && c.is_ascii_graphic() => {
+                self.host.handle_event(event);
+                self.leave_hosts();
+            },
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => {
+                self.host.handle_event(event);
+                self.leave_hosts();
+            },
+            KeyCode::Tab => self.focus_other_widget(),
+            KeyCode::Up => {
+              self.selected_field = match self.selected_field {
+                Field::Signup => Field::RemoteLogin,
+                Field::RemoteLogin => Field::LocalLogin,
+                _ if self.hosts.is_empty() => Field::LocalLogin,
+                Field::Hosts => {
+                  self.select_host(self.host_index.saturating_sub(1));
+                  Field::Hosts
                 },
-                KeyCode::Tab => self.focus_other_widget(),
-                KeyCode::Up => {
-                  self.selected_field = Field::LocalLogin;
-                  self.needs_update = true;
-                },
-                KeyCode::Down => {
-                  self.selected_field = Field::RemoteLogin;
-                  self.needs_update = true;
+                Field::LocalLogin => {
+                  self.select_host(self.host_index);
+                  Field::Hosts
                 },
-                KeyCode::Enter => {
-                    if self.host.value().is_empty() {
-                      self.error_message = Some("Host can't be empty".to_string());
-                      self.needs_update = true;
-                      return None;
-                    }
-
-                    match self.selected_field {
-                        Field::LocalLogin => return Some(PageResults::HostSelected((self.host.value().to_owned(), LoginType::LocalLogin))),
-                        Field::RemoteLogin => return Some(PageResults::HostSelected((self.host.value().to_owned(), LoginType::RemoteLogin))),
-                    }
+              };
+              self.needs_update = true;
+            },
+            KeyCode::Down => {
+              self.selected_field = match self.selected_field {
+                Field::Hosts if self.host_index + 1 < self.hosts.len() => {
+                  self.select_host(self.host_index + 1);
+                  Field::Hosts
                 },
-                _ => (),
+                Field::Hosts => Field::LocalLogin,
+                Field::LocalLogin => Field::RemoteLogin,
+                _ => Field::Signup,
+              };
+              self.needs_update = true;
+            },
+            KeyCode::Enter => return self.submit(),
+            _ => (),
+        }
+        None
+    }
+
+    fn rename_key_event(&mut self, event: &Event, code: KeyCode) -> Option<PageResults> {
+        let name = self.renaming.as_mut()?;
+        match code {
+            KeyCode::Esc => self.renaming = None,
+            KeyCode::Enter => {
+                let name = Some(name.value().trim().to_string()).filter(|name| !name.is_empty());
+                self.renaming = None;
+                self.needs_update = true;
+                let url = self.hosts.get(self.host_index)?.url.clone();
+                return Some(PageResults::Host(HostRequest::Rename(url, name)));
+            }
+            KeyCode::Char(_) if name.value().chars().count() >= NAME_MAX_LENGTH => (),
+            _ => {
+                name.handle_event(event);
             }
         }
+        self.needs_update = true;
         None
     }
 
+    pub fn mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        let position = Position::new(event.column, event.row);
+        if self.renaming.is_none() && self.hosts_area.contains(position) {
+            return self.hosts_mouse_event(event);
+        }
+
+        let (field, _) = self
+            .option_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))?;
+
+        if self.selected_field.ne(field) {
+            self.selected_field = field.clone();
+            self.needs_update = true;
+        }
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.submit(),
+            _ => None,
+        }
+    }
+
+    fn hosts_mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        let row = event.row.checked_sub(self.hosts_area.y + 1)? as usize;
+        let index = self.host_index.saturating_sub(LIST_ROWS - 1) + row;
+        if row >= LIST_ROWS || index >= self.hosts.len() {
+            return None;
+        }
+
+        if self.selected_field != Field::Hosts || self.host_index != index {
+            self.selected_field = Field::Hosts;
+            self.select_host(index);
+            self.needs_update = true;
+        }
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.submit(),
+            _ => None,
+        }
+    }
+
+    /// Selects the saved host at `index` and puts its URL into the input.
+    fn select_host(&mut self, index: usize) {
+        if let Some(host) = self.hosts.get(index) {
+            self.host_index = index;
+            self.host = Input::default().with_value(host.url.clone());
+        }
+    }
+
+    /// Typing edits the input, so the login buttons act on it from then on.
+    fn leave_hosts(&mut self) {
+        if self.selected_field == Field::Hosts {
+            self.selected_field = Field::LocalLogin;
+        }
+        self.needs_update = true;
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        let host = match Server::parse(self.host.value()) {
+          Ok(host) => host,
+          Err(err) => {
+            self.error_message = Some(err.to_string());
+            self.needs_update = true;
+            return None;
+          }
+        };
+
+        match self.selected_field {
+            Field::Hosts => {
+                let login = self.hosts.get(self.host_index)?.login;
+                Some(PageResults::HostSelected((host, login.login_type())))
+            },
+            Field::LocalLogin => Some(PageResults::HostSelected((host, LoginType::LocalLogin))),
+            Field::RemoteLogin => Some(PageResults::HostSelected((host, LoginType::RemoteLogin))),
+            Field::Signup => Some(PageResults::HostSelected((host, LoginType::Signup))),
+        }
+    }
+
     pub fn host_error(&mut self, error: &LoginErrors) {
         self.needs_update = true;
+        self.info_message = None;
         self.error_message = Some(error.to_string());
     }
 
+    pub fn host_info(&mut self, message: Option<String>) {
+        self.needs_update = true;
+        self.info_message = message;
+    }
+
     fn focus_other_widget(&mut self) {
         self.needs_update = true;
         self.selected_field = match self.selected_field {
+            Field::Hosts => Field::LocalLogin,
             Field::LocalLogin=> Field::RemoteLogin,
-            Field::RemoteLogin => Field::LocalLogin,
+            Field::RemoteLogin => Field::Signup,
+            Field::Signup if self.hosts.is_empty() => Field::LocalLogin,
+            Field::Signup => {
+                self.select_host(self.host_index);
+                Field::Hosts
+            },
         }
     }
 
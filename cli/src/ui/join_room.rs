@@ -2,14 +2,14 @@ use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout},
-    style::{Color, Style},
-    widgets::{Block, Padding, Paragraph},
+    style::Style,
+    widgets::{Padding, Paragraph},
 };
 use tui_input::{Input, backend::crossterm::EventHandler};
 
 use crate::websocket::events::errors::EventError;
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
 #[derive(Debug, Clone)]
 pub struct JoinRoomPage {
@@ -27,7 +27,14 @@ impl JoinRoomPage {
         }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn with_room(room_id: &str) -> Self {
+        Self {
+            input: Input::default().with_value(room_id.to_string()),
+            ..Self::new()
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -42,12 +49,13 @@ impl JoinRoomPage {
         ])
         .areas(horizontal);
 
-        let style: Style = Color::Yellow.into();
+        let style: Style = theme.input.into();
         let input = Paragraph::new(self.input.value())
             .style(style)
             // .scroll((0, scroll as u16))
             .block(
-                Block::bordered()
+                theme
+                    .block()
                     .title("Room ID")
                     .padding(Padding::left(17)),
             );
@@ -57,10 +65,10 @@ impl JoinRoomPage {
         frame.set_cursor_position((area.x + x as u16, area.y + 1));
 
         if let Some(msg) = self.error_message.as_ref() {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
             frame.render_widget(input, error);
         }
 
@@ -73,21 +81,17 @@ impl JoinRoomPage {
         if let Event::Key(key) = &mut event {
             match &mut key.code {
                 KeyCode::Esc => return Some(PageResults::BackToMenu),
-                KeyCode::Char(c) => {
-                    if self.input.value().len() < 6 && c.is_ascii_alphanumeric() {
-                        *c = c.to_ascii_uppercase();
-                        self.input.handle_event(&event);
-                        self.needs_update = true;
-                    }
+                KeyCode::Char(c) if self.input.value().len() < 6 && c.is_ascii_alphanumeric() => {
+                    *c = c.to_ascii_uppercase();
+                    self.input.handle_event(&event);
+                    self.needs_update = true;
                 }
                 KeyCode::Backspace => {
                     self.input.handle_event(&event);
                     self.needs_update = true;
                 }
-                KeyCode::Enter => {
-                    if self.input.value().len() == 6 {
-                        return Some(PageResults::JoinRoom(self.input.value().to_owned()));
-                    }
+                KeyCode::Enter if self.input.value().len() == 6 => {
+                    return Some(PageResults::JoinRoom(self.input.value().to_owned()));
                 }
                 _ => (),
             }
@@ -2,14 +2,14 @@ use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    style::Style,
+    widgets::Paragraph,
 };
 use tui_input::{Input, backend::crossterm::EventHandler};
 
 use crate::auth::LoginErrors;
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
 #[derive(Debug, PartialEq, Clone)]
 enum Field {
@@ -26,12 +26,6 @@ pub struct LoginPage {
     error_message: Option<String>,
 }
 
-#[derive(Debug)]
-pub enum LoginResult {
-    Login((String, String)),
-    Abort,
-}
-
 impl LoginPage {
     pub fn new() -> Self {
         Self {
@@ -43,16 +37,18 @@ impl LoginPage {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_input_field(
         &self,
         frame: &mut Frame,
+        theme: &Theme,
         rect: Rect,
         field: &Input,
         title: &str,
         focused: bool,
         password: bool,
     ) {
-        let style: Style = Color::Yellow.into();
+        let style: Style = theme.input.into();
 
         let text = match password {
             true => (0..field.value().len()).map(|_| '*').collect(),
@@ -61,7 +57,7 @@ impl LoginPage {
 
         let input = Paragraph::new(text)
             .style(style)
-            .block(Block::bordered().title(title));
+            .block(theme.block().title(title));
 
         frame.render_widget(input, rect);
 
@@ -71,7 +67,7 @@ impl LoginPage {
         }
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -89,6 +85,7 @@ impl LoginPage {
 
         self.render_input_field(
             frame,
+            theme,
             email,
             &self.email,
             "E-Mail",
@@ -97,6 +94,7 @@ impl LoginPage {
         );
         self.render_input_field(
             frame,
+            theme,
             password,
             &self.password,
             "Password",
@@ -105,10 +103,10 @@ impl LoginPage {
         );
 
         if let Some(msg) = &self.error_message {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
             frame.render_widget(input, error);
         }
         self.needs_update = false;
@@ -123,11 +121,9 @@ impl LoginPage {
         if let Event::Key(key) = event {
             match key.code {
                 KeyCode::Esc => return Some(PageResults::Exit),
-                KeyCode::Char(c) => {
-                    if current_widget.value().len() < 32 && c.is_ascii_graphic() {
-                        current_widget.handle_event(event);
-                        self.needs_update = true;
-                    }
+                KeyCode::Char(c) if current_widget.value().len() < 32 && c.is_ascii_graphic() => {
+                    current_widget.handle_event(event);
+                    self.needs_update = true;
                 }
                 KeyCode::Backspace => {
                     current_widget.handle_event(event);
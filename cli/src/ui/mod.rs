@@ -1,11 +1,20 @@
+pub mod account;
+pub mod account_switcher;
 pub mod game;
+pub mod graphics;
 pub mod game_lobby;
 pub mod game_over;
 pub mod gamemode;
 pub mod join_room;
 pub mod login;
 pub mod pages;
+pub mod pause_menu;
+pub mod remote_login;
+pub mod security;
+pub mod settings;
+pub mod signup;
 pub mod widgets;
 pub mod totp;
 pub mod host_selector;
 pub mod nickname_page;
+pub mod theme;
@@ -2,14 +2,14 @@ use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    style::Style,
+    widgets::Paragraph,
 };
 use tui_input::{Input, backend::crossterm::EventHandler};
 
 use crate::auth::LoginErrors;
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
 #[derive(Debug, Clone)]
 pub struct NicknamePage {
@@ -30,17 +30,18 @@ impl NicknamePage {
     fn render_input_field(
         &self,
         frame: &mut Frame,
+        theme: &Theme,
         rect: Rect,
         field: &Input,
         title: &str,
     ) {
-        let style: Style = Color::Yellow.into();
+        let style: Style = theme.input.into();
 
         let text = field.value().to_owned();
 
         let input = Paragraph::new(text)
             .style(style)
-            .block(Block::bordered().title(title));
+            .block(theme.block().title(title));
 
         frame.render_widget(input, rect);
 
@@ -48,7 +49,7 @@ impl NicknamePage {
         frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -65,16 +66,17 @@ impl NicknamePage {
 
         self.render_input_field(
             frame,
+            theme,
             nickname,
             &self.nickname,
             "Nickname",
         );
 
         if let Some(msg) = &self.error_message {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
             frame.render_widget(input, error);
         }
         self.needs_update = false;
@@ -84,11 +86,9 @@ impl NicknamePage {
         if let Event::Key(key) = event {
             match key.code {
                 KeyCode::Esc => return Some(PageResults::Exit),
-                KeyCode::Char(c) => {
-                    if self.nickname.value().len() < 32 && c.is_ascii_graphic() {
-                          self.nickname.handle_event(event);
-                          self.needs_update = true;
-                    }
+                KeyCode::Char(c) if self.nickname.value().len() < 32 && c.is_ascii_graphic() => {
+                    self.nickname.handle_event(event);
+                    self.needs_update = true;
                 },
                 KeyCode::Backspace => {
                     self.nickname.handle_event(event);
@@ -1,10 +1,13 @@
-use crossterm::event::{Event, KeyEventKind};
+use crossterm::event::{Event, KeyEventKind, MouseEvent};
 use ratatui::Frame;
 
-use crate::websocket::events::request::PaddleMoveDirection;
+use crate::{config::Settings, game::keymap::Keymap, server::Server, websocket::events::request::PaddleMoveDirection};
 
 use super::{
-    game::Game, game_lobby::GameLobbyPage, game_over::GameOverPage, gamemode::{GameModePage, GameModes}, host_selector::HostSelectorPage, join_room::JoinRoomPage, login::LoginPage, nickname_page::NicknamePage, totp::TotpPage
+    account::{AccountPage, AccountRequest},
+    account_switcher::{AccountChoice, AccountSwitcherPage},
+    theme::Theme,
+    game::Game, game_lobby::GameLobbyPage, game_over::GameOverPage, gamemode::{GameModePage, GameModes}, host_selector::{HostRequest, HostSelectorPage}, join_room::JoinRoomPage, login::LoginPage, nickname_page::NicknamePage, remote_login::RemoteLoginPage, security::SecurityPage, settings::SettingsPage, signup::SignupPage, totp::TotpPage
 };
 
 #[derive(Debug, Clone)]
@@ -12,26 +15,38 @@ pub enum Pages {
     HostSelector(HostSelectorPage),
     NicknameSelector(NicknamePage),
     Login(LoginPage),
+    RemoteLogin(RemoteLoginPage),
+    Signup(SignupPage),
     TotpPage(TotpPage),
+    AccountSwitcher(AccountSwitcherPage),
     GameModeSelector(GameModePage),
+    Security(SecurityPage),
+    Account(AccountPage),
+    Settings(SettingsPage),
     JoinRoom(JoinRoomPage),
     GameLobby(GameLobbyPage),
-    Game(Game),
-    GameOver(GameOverPage),
+    Game(Box<Game>),
+    GameOver(Box<GameOverPage>),
 }
 
 impl Pages {
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         match self {
-            Self::HostSelector(page) => page.render(frame),
-            Self::NicknameSelector(page) => page.render(frame),
-            Self::Login(page) => page.render(frame),
-            Self::TotpPage(page) => page.render(frame),
-            Self::GameModeSelector(page) => page.render(frame),
-            Self::JoinRoom(page) => page.render(frame),
-            Self::GameLobby(page) => page.render(frame),
-            Self::Game(page) => page.render(frame),
-            Self::GameOver(page) => page.render(frame),
+            Self::HostSelector(page) => page.render(frame, theme),
+            Self::NicknameSelector(page) => page.render(frame, theme),
+            Self::Login(page) => page.render(frame, theme),
+            Self::RemoteLogin(page) => page.render(frame, theme),
+            Self::Signup(page) => page.render(frame, theme),
+            Self::TotpPage(page) => page.render(frame, theme),
+            Self::AccountSwitcher(page) => page.render(frame, theme),
+            Self::GameModeSelector(page) => page.render(frame, theme),
+            Self::Security(page) => page.render(frame, theme),
+            Self::Account(page) => page.render(frame, theme),
+            Self::Settings(page) => page.render(frame, theme),
+            Self::JoinRoom(page) => page.render(frame, theme),
+            Self::GameLobby(page) => page.render(frame, theme),
+            Self::Game(page) => page.render(frame, theme),
+            Self::GameOver(page) => page.render(frame, theme),
         }
     }
 
@@ -40,8 +55,14 @@ impl Pages {
             (Self::HostSelector(page), KeyEventKind::Press) => page.key_event(event),
             (Self::NicknameSelector(page), KeyEventKind::Press) => page.key_event(event),
             (Self::Login(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::RemoteLogin(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::Signup(page), KeyEventKind::Press) => page.key_event(event),
             (Self::TotpPage(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::AccountSwitcher(page), KeyEventKind::Press) => page.key_event(event),
             (Self::GameModeSelector(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::Security(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::Account(page), KeyEventKind::Press) => page.key_event(event),
+            (Self::Settings(page), KeyEventKind::Press) => page.key_event(event),
             (Self::JoinRoom(page), KeyEventKind::Press) => page.key_event(event),
             (Self::GameLobby(page), KeyEventKind::Press) => page.key_event(event),
             (Self::Game(page), _) => page.key_event(event),
@@ -50,13 +71,29 @@ impl Pages {
         }
     }
 
+    pub fn mouse_event(&mut self, event: &MouseEvent) -> Option<PageResults> {
+        match self {
+            Self::HostSelector(page) => page.mouse_event(event),
+            Self::AccountSwitcher(page) => page.mouse_event(event),
+            Self::GameModeSelector(page) => page.mouse_event(event),
+            Self::Game(page) => page.mouse_event(event),
+            _ => None,
+        }
+    }
+
     pub fn needs_update(&self) -> bool {
         match self {
             Self::HostSelector(page) => page.needs_update(),
             Self::NicknameSelector(page) => page.needs_update(),
             Self::Login(loginpage) => loginpage.needs_update(),
+            Self::RemoteLogin(page) => page.needs_update(),
+            Self::Signup(page) => page.needs_update(),
             Self::TotpPage(page) => page.needs_update(),
+            Self::AccountSwitcher(page) => page.needs_update(),
             Self::GameModeSelector(gamemodepage) => gamemodepage.needs_update(),
+            Self::Security(page) => page.needs_update(),
+            Self::Account(page) => page.needs_update(),
+            Self::Settings(page) => page.needs_update(),
             Self::JoinRoom(joinroompage) => joinroompage.needs_update(),
             Self::GameLobby(page) => page.needs_update(),
             Self::Game(game) => game.needs_update(),
@@ -65,23 +102,39 @@ impl Pages {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum LoginType {
   LocalLogin,
   RemoteLogin,
+  Signup,
 }
 
 #[derive(Debug)]
 pub enum PageResults {
-    HostSelected((String, LoginType)),
+    HostSelected((Server, LoginType)),
+    Host(HostRequest),
     NicknameSelected(String),
     Login((String, String)),
+    RemoteCallback((String, bool)),
+    CancelRemoteLogin,
+    CopyToClipboard(String),
+    Signup((String, String, String)),
     Totp(String),
+    SetTotp((bool, String)),
+    Account(AccountRequest),
+    Accounts(AccountChoice),
+    SaveSettings(Settings),
     BackToMenu,
     GameModeChosen(GameModes),
     JoinRoom(String),
     UpdatePaddleMovement((PaddleMoveDirection, PaddleMoveDirection)),
     GamePaused(bool),
     GameOver,
+    Rematch,
+    CancelRematch,
+    ThemeSelected(String),
+    KeymapChanged(Keymap),
+    ForgetSession,
+    Logout,
     Exit,
 }
@@ -0,0 +1,179 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+};
+
+use crate::game::keymap::{ACTIONS, Keymap};
+
+use super::theme::Theme;
+
+const MENU_ITEMS: [&str; 3] = ["Resume", "Settings", "Leave match"];
+
+#[derive(Debug, Clone)]
+enum MenuState {
+    Menu(usize),
+    Settings(usize),
+    Rebinding(usize),
+    ConfirmLeave(bool),
+}
+
+#[derive(Debug)]
+pub enum MenuAction {
+    Resume,
+    Leave,
+    ThemeSelected(String),
+    KeymapChanged(Keymap),
+}
+
+/// Modal overlay of a running match.
+#[derive(Debug, Clone)]
+pub struct PauseMenu {
+    state: MenuState,
+    themes: Vec<String>,
+    theme: usize,
+    keymap: Keymap,
+}
+
+impl PauseMenu {
+    pub fn new(themes: Vec<String>, theme: &str, keymap: Keymap) -> Self {
+        let theme = themes.iter().position(|name| name == theme).unwrap_or(0);
+        Self {
+            state: MenuState::Menu(0),
+            themes,
+            theme,
+            keymap,
+        }
+    }
+
+    // Settings rows: theme, one per key binding, back
+    fn settings_len(&self) -> usize {
+        ACTIONS.len() + 2
+    }
+
+    pub fn key_event(&mut self, key: &KeyEvent) -> Option<MenuAction> {
+        match (self.state.clone(), key.code) {
+            (MenuState::Menu(_), KeyCode::Esc) => return Some(MenuAction::Resume),
+            (MenuState::Menu(i), KeyCode::Up) => self.state = MenuState::Menu(i.saturating_sub(1)),
+            (MenuState::Menu(i), KeyCode::Down) => {
+                self.state = MenuState::Menu((i + 1).min(MENU_ITEMS.len() - 1))
+            }
+            (MenuState::Menu(0), KeyCode::Enter) => return Some(MenuAction::Resume),
+            (MenuState::Menu(1), KeyCode::Enter) => self.state = MenuState::Settings(0),
+            (MenuState::Menu(_), KeyCode::Enter) => self.state = MenuState::ConfirmLeave(false),
+
+            (MenuState::Settings(_), KeyCode::Esc) => self.state = MenuState::Menu(1),
+            (MenuState::Settings(i), KeyCode::Up) => {
+                self.state = MenuState::Settings(i.saturating_sub(1))
+            }
+            (MenuState::Settings(i), KeyCode::Down) => {
+                self.state = MenuState::Settings((i + 1).min(self.settings_len() - 1))
+            }
+            (MenuState::Settings(0), KeyCode::Left | KeyCode::Right | KeyCode::Enter)
+                if !self.themes.is_empty() =>
+            {
+                self.theme = match key.code {
+                    KeyCode::Left => (self.theme + self.themes.len() - 1) % self.themes.len(),
+                    _ => (self.theme + 1) % self.themes.len(),
+                };
+                return Some(MenuAction::ThemeSelected(self.themes[self.theme].clone()));
+            }
+            (MenuState::Settings(i), KeyCode::Enter) if i == self.settings_len() - 1 => {
+                self.state = MenuState::Menu(1)
+            }
+            (MenuState::Settings(i), KeyCode::Enter) if i > 0 => {
+                self.state = MenuState::Rebinding(i - 1)
+            }
+
+            (MenuState::Rebinding(i), KeyCode::Esc) => self.state = MenuState::Settings(i + 1),
+            (MenuState::Rebinding(i), code) => {
+                self.keymap.set(ACTIONS[i], code);
+                self.state = MenuState::Settings(i + 1);
+                return Some(MenuAction::KeymapChanged(self.keymap));
+            }
+
+            (MenuState::ConfirmLeave(_), KeyCode::Esc) => self.state = MenuState::Menu(2),
+            (MenuState::ConfirmLeave(leave), KeyCode::Left | KeyCode::Right | KeyCode::Tab) => {
+                self.state = MenuState::ConfirmLeave(!leave)
+            }
+            (MenuState::ConfirmLeave(true), KeyCode::Enter) => return Some(MenuAction::Leave),
+            (MenuState::ConfirmLeave(false), KeyCode::Enter) => self.state = MenuState::Menu(2),
+            _ => (),
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let (title, lines) = match self.state {
+            MenuState::Menu(selected) => (
+                "Paused",
+                MENU_ITEMS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| Line::styled(*item, theme.selection(i == selected)))
+                    .collect(),
+            ),
+            MenuState::Settings(selected) => ("Settings", self.settings_lines(selected, theme)),
+            MenuState::Rebinding(i) => (
+                "Settings",
+                vec![
+                    Line::styled(format!("Press a key for \"{}\"", ACTIONS[i].label()), theme.text),
+                    Line::styled("Esc to cancel", theme.muted),
+                ],
+            ),
+            MenuState::ConfirmLeave(leave) => (
+                "Leave match",
+                vec![
+                    Line::styled("Leaving forfeits the match.", theme.text),
+                    Line::default(),
+                    Line::from(vec![
+                        Span::styled("Stay", theme.selection(!leave)),
+                        Span::raw("    "),
+                        Span::styled("Leave", theme.selection(leave)),
+                    ]),
+                ],
+            ),
+        };
+
+        let [_, vertical, _] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(lines.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        let [_, popup, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(36),
+            Constraint::Min(0),
+        ])
+        .areas(vertical);
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines).centered().block(theme.block().title(title)),
+            popup,
+        );
+    }
+
+    fn settings_lines(&self, selected: usize, theme: &Theme) -> Vec<Line<'static>> {
+        let theme_name = self.themes.get(self.theme).cloned().unwrap_or_default();
+        let mut lines = vec![Line::styled(
+            format!("Theme: < {} >", theme_name),
+            theme.selection(selected == 0),
+        )];
+
+        lines.extend(ACTIONS.iter().enumerate().map(|(i, action)| {
+            Line::styled(
+                format!("{}: {}", action.label(), self.keymap.get(*action)),
+                theme.selection(selected == i + 1),
+            )
+        }));
+        lines.push(Line::styled(
+            "Back",
+            theme.selection(selected == self.settings_len() - 1),
+        ));
+        lines
+    }
+}
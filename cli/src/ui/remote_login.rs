@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::auth;
+
+use super::{pages::PageResults, theme::Theme, widgets::qr};
+
+/// Time the user has to finish the login on the 42 intranet.
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const PASTE_MAX_LENGTH: usize = 4096;
+
+/// Waits for the callback of a login through 42. Without a local browser the
+/// URL can be opened elsewhere, and the callback URL or token pasted back.
+#[derive(Debug, Clone)]
+pub struct RemoteLoginPage {
+    url: Option<String>,
+    qr_code: Option<Vec<Line<'static>>>,
+    port: u16,
+    /// State of the login, a pasted callback URL has to carry it.
+    state: String,
+    browser_opened: bool,
+    started: Instant,
+    shown_seconds: u64,
+    paste: Input,
+    needs_update: bool,
+    error_message: Option<String>,
+    info_message: Option<String>,
+}
+
+impl RemoteLoginPage {
+    /// `port` is where the local listener waits for the callback of the login
+    /// started with `state`.
+    pub fn new(port: u16, state: String) -> Self {
+        Self {
+            url: None,
+            qr_code: None,
+            port,
+            state,
+            browser_opened: false,
+            started: Instant::now(),
+            shown_seconds: TIMEOUT.as_secs(),
+            paste: Input::default(),
+            needs_update: true,
+            error_message: None,
+            info_message: None,
+        }
+    }
+
+    pub fn set_url(&mut self, url: String, browser_opened: bool) {
+        self.qr_code = qr::half_blocks(&url);
+        self.url = Some(url);
+        self.browser_opened = browser_opened;
+        self.needs_update = true;
+    }
+
+    pub fn has_url(&self) -> bool {
+        self.url.is_some()
+    }
+
+    fn remaining(&self) -> Duration {
+        TIMEOUT.saturating_sub(self.started.elapsed())
+    }
+
+    pub fn expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let qr_size = match (&self.qr_code, theme.ascii_borders) {
+            (Some(lines), false) => Some(qr::size(lines)),
+            (_, _) => None,
+        };
+        let (qr_width, qr_height) = qr_size.unwrap_or_default();
+        let width = qr_width.max(72).min(frame.area().width);
+
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let url_height = match &self.url {
+            Some(url) => (url.len() as u16).div_ceil(width.saturating_sub(2).max(1)) + 2,
+            None => 3,
+        };
+        // The code is left out when it doesn't fit, the URL is still there
+        let qr_height = match frame.area().height > qr_height + url_height + 12 {
+            true => qr_height,
+            false => 0,
+        };
+
+        let [status, url, qr_area, paste, message] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(url_height),
+            Constraint::Length(qr_height),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+        self.shown_seconds = self.remaining().as_secs();
+        let status_text = match (&self.url, self.browser_opened) {
+            (None, _) => "Requesting the login URL...".to_string(),
+            (Some(_), true) => "Continue in the browser that just opened".to_string(),
+            (Some(_), false) => format!("Open the URL below, the callback goes to localhost:{}", self.port),
+        };
+        let countdown = format!("{}:{:02} left", self.shown_seconds / 60, self.shown_seconds % 60);
+        let widget = Paragraph::new(status_text).style(Style::from(theme.text)).block(
+            theme
+                .block()
+                .title("Login through 42")
+                .title_bottom(format!("Esc: cancel  Ctrl+Y: copy URL  {}", countdown)),
+        );
+        frame.render_widget(widget, status);
+
+        if let Some(text) = &self.url {
+            // Plain wrapped text so the terminal can select it
+            let widget = Paragraph::new(text.clone())
+                .style(Style::from(theme.highlight))
+                .wrap(Wrap { trim: false })
+                .block(theme.block().title("Authorization URL"));
+            frame.render_widget(widget, url);
+        }
+
+        if let (Some(lines), true) = (&self.qr_code, qr_height > 0) {
+            let [_, centered, _] = Layout::horizontal([
+                Constraint::Min(0),
+                Constraint::Length(qr_width),
+                Constraint::Min(0),
+            ])
+            .areas(qr_area);
+            frame.render_widget(Paragraph::new(lines.clone()), centered);
+        }
+
+        self.render_paste(frame, theme, paste);
+
+        if let Some(msg) = &self.error_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.error))
+                .block(theme.block().title("Error"));
+            frame.render_widget(widget, message);
+        } else if let Some(msg) = &self.info_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.text))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        } else if !self.browser_opened && self.url.is_some() {
+            let hint = format!(
+                "Over SSH: ssh -L {0}:localhost:{0} <this host>, or paste what the page shows",
+                self.port
+            );
+            let widget = Paragraph::new(hint)
+                .style(Style::from(theme.muted))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        }
+
+        self.needs_update = false;
+    }
+
+    fn render_paste(&self, frame: &mut Frame, theme: &Theme, rect: Rect) {
+        let width = rect.width.saturating_sub(2) as usize;
+        let scroll = self.paste.visual_scroll(width.saturating_sub(1));
+        let widget = Paragraph::new(self.paste.value().to_owned())
+            .style(Style::from(theme.input))
+            .scroll((0, scroll as u16))
+            .block(theme.block().title("Paste the callback URL or token"));
+        frame.render_widget(widget, rect);
+
+        let x = self.paste.visual_cursor().saturating_sub(scroll) + 1;
+        frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        match key.code {
+            KeyCode::Esc => return Some(PageResults::CancelRemoteLogin),
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return self.url.clone().map(PageResults::CopyToClipboard);
+            }
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Char(_) if self.paste.value().len() >= PASTE_MAX_LENGTH => (),
+            _ => {
+                self.paste.handle_event(event);
+                self.needs_update = true;
+            }
+        }
+        None
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        match parse_callback(self.paste.value(), &self.state) {
+            Ok(callback) => Some(PageResults::RemoteCallback(callback)),
+            Err(message) => {
+                self.error(message);
+                None
+            }
+        }
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    /// Also true once a second for the countdown.
+    pub fn needs_update(&self) -> bool {
+        self.needs_update || self.remaining().as_secs() != self.shown_seconds
+    }
+}
+
+/// Token and whether a nickname is still needed, from the callback URL the
+/// web page calls or from the bare token. A URL has to belong to the login
+/// started with `state`.
+fn parse_callback(input: &str, state: &str) -> Result<(String, bool), String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Paste the callback URL or the token first".to_string());
+    }
+
+    let (token, nickname_required) = match input.split_once('?') {
+        Some((_, query)) => {
+            let params: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+            if params.get("state").map(String::as_str) != Some(state) {
+                return Err("The URL belongs to another login, start over from this one".to_string());
+            }
+            let token = params
+                .get("token")
+                .cloned()
+                .ok_or("The URL has no token parameter".to_string())?;
+            (token, params.get("nickname_required").map(|value| value == "true"))
+        }
+        None => (input.to_string(), None),
+    };
+
+    match (auth::nickname_required(&token), nickname_required) {
+        (None, _) => Err("That is not a valid token".to_string()),
+        (Some(_), Some(nickname_required)) => Ok((token, nickname_required)),
+        (Some(nickname_required), None) => Ok((token, nickname_required)),
+    }
+}
@@ -0,0 +1,201 @@
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::api::endpoints::AccountResponse;
+
+use super::{pages::PageResults, theme::Theme, widgets::qr};
+
+const TOTP_CODE_LENGTH: usize = 6;
+
+/// Enrollment in and removal of two-factor authentication.
+#[derive(Debug, Clone)]
+pub struct SecurityPage {
+    account: Option<AccountResponse>,
+    qr_code: Option<Vec<Line<'static>>>,
+    code: Input,
+    pending: bool,
+    needs_update: bool,
+    error_message: Option<String>,
+    info_message: Option<String>,
+}
+
+impl SecurityPage {
+    pub fn new() -> Self {
+        Self {
+            account: None,
+            qr_code: None,
+            code: Input::default(),
+            pending: true,
+            needs_update: true,
+            error_message: None,
+            info_message: None,
+        }
+    }
+
+    pub fn set_account(&mut self, account: AccountResponse) {
+        self.qr_code = account.enable_totp_uri.as_deref().and_then(qr::half_blocks);
+        self.account = Some(account);
+        self.code.reset();
+        self.pending = false;
+        self.needs_update = true;
+    }
+
+    /// Base32 secret of the otpauth URI, for apps that can't scan the code.
+    fn secret(&self) -> Option<String> {
+        let uri = self.account.as_ref()?.enable_totp_uri.as_deref()?;
+        let uri = url::Url::parse(uri).ok()?;
+        uri.query_pairs()
+            .find(|(key, _)| key == "secret")
+            .map(|(_, secret)| secret.into_owned())
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let qr_size = match (&self.qr_code, theme.ascii_borders) {
+            (Some(lines), false) => Some(qr::size(lines)),
+            (_, _) => None,
+        };
+        let (qr_width, qr_height) = qr_size.unwrap_or_default();
+
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(qr_width.max(46)),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let [status, qr_area, secret, code, message] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(qr_height),
+            Constraint::Length(4),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+        let status_text = match (&self.account, self.pending) {
+            (_, true) => "Loading...".to_string(),
+            (Some(account), false) if account.totp_enabled => "Two-factor authentication is on".to_string(),
+            (Some(_), false) => "Two-factor authentication is off".to_string(),
+            (None, false) => "Account details are unavailable".to_string(),
+        };
+        let status_widget = Paragraph::new(status_text)
+            .style(Style::from(theme.text))
+            .block(theme.block().title("Security").title_bottom("Esc: back"));
+        frame.render_widget(status_widget, status);
+
+        if let (Some(lines), Some(_)) = (&self.qr_code, qr_size) {
+            let [_, centered, _] = Layout::horizontal([
+                Constraint::Min(0),
+                Constraint::Length(qr_width),
+                Constraint::Min(0),
+            ])
+            .areas(qr_area);
+            frame.render_widget(Paragraph::new(lines.clone()), centered);
+        }
+
+        if let Some(secret_value) = self.secret() {
+            let text = vec![
+                Line::styled(secret_value, theme.highlight),
+                Line::styled("Scan the code or enter the secret in an app", theme.muted),
+            ];
+            let widget = Paragraph::new(text)
+                .wrap(Wrap { trim: true })
+                .block(theme.block().title("Secret"));
+            frame.render_widget(widget, secret);
+        }
+
+        if let Some(account) = &self.account {
+            let title = match account.totp_enabled {
+                true => "Code to turn 2FA off",
+                false => "Code to turn 2FA on",
+            };
+            self.render_code(frame, theme, code, title);
+        }
+
+        if let Some(msg) = &self.error_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.error))
+                .block(theme.block().title("Error"));
+            frame.render_widget(widget, message);
+        } else if let Some(msg) = &self.info_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .style(Style::from(theme.text))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        }
+
+        self.needs_update = false;
+    }
+
+    fn render_code(&self, frame: &mut Frame, theme: &Theme, rect: Rect, title: &str) {
+        let input = Paragraph::new(self.code.value().to_owned())
+            .style(Style::from(theme.input))
+            .block(theme.block().title(title.to_owned()));
+        frame.render_widget(input, rect);
+
+        if !self.pending {
+            let x = self.code.value().len() + 1;
+            frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
+        }
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        if let Event::Key(key) = event {
+            match key.code {
+                KeyCode::Esc => return Some(PageResults::BackToMenu),
+                KeyCode::Char(c) if self.code.value().len() < TOTP_CODE_LENGTH && c.is_ascii_digit() => {
+                    self.code.handle_event(event);
+                    self.needs_update = true;
+                }
+                KeyCode::Backspace => {
+                    self.code.handle_event(event);
+                    self.needs_update = true;
+                }
+                KeyCode::Enter => return self.submit(),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        let account = self.account.as_ref()?;
+        if self.pending {
+            return None;
+        }
+        if self.code.value().len() != TOTP_CODE_LENGTH {
+            self.error(format!("The code has {} digits", TOTP_CODE_LENGTH));
+            return None;
+        }
+
+        self.pending = true;
+        self.needs_update = true;
+        Some(PageResults::SetTotp((!account.totp_enabled, self.code.value().to_owned())))
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.pending = false;
+        self.code.reset();
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    pub fn needs_update(&self) -> bool {
+        self.needs_update
+    }
+}
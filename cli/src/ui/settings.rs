@@ -0,0 +1,298 @@
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::config::{DEFAULT_FPS, DEFAULT_MOVEMENT_TIMEOUT_MS, GraphicsMode, Profile, Settings, StartNotification};
+
+use super::{pages::PageResults, theme::Theme};
+
+const LABEL_WIDTH: usize = 20;
+const VALUE_MAX_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Host,
+    Theme,
+    Keymap,
+    Fps,
+    MovementTimeout,
+    Graphics,
+    StartNotification,
+    RememberSession,
+}
+
+const FIELDS: [(Field, &str); 8] = [
+    (Field::Host, "Host"),
+    (Field::Theme, "Theme"),
+    (Field::Keymap, "Keymap"),
+    (Field::Fps, "Frames per second"),
+    (Field::MovementTimeout, "Movement timeout"),
+    (Field::Graphics, "Graphics"),
+    (Field::StartNotification, "Start notification"),
+    (Field::RememberSession, "Remember session"),
+];
+
+/// Editor for the top-level values of the config file.
+#[derive(Debug, Clone)]
+pub struct SettingsPage {
+    selection: usize,
+    host: Input,
+    theme: Option<String>,
+    themes: Vec<String>,
+    keymap: Input,
+    fps: Input,
+    movement_timeout: Input,
+    /// Not edited here, kept so saving doesn't drop it.
+    callback_port: Option<u16>,
+    graphics: GraphicsMode,
+    start_notification: StartNotification,
+    remember_session: bool,
+    path: String,
+    needs_update: bool,
+    error_message: Option<String>,
+    info_message: Option<String>,
+}
+
+impl SettingsPage {
+    pub fn new(settings: Settings, themes: Vec<String>, path: String) -> Self {
+        let values = settings.values;
+        let input = |value: Option<String>| Input::new(value.unwrap_or_default());
+        Self {
+            selection: 0,
+            host: input(values.host),
+            theme: values.theme,
+            themes,
+            keymap: input(values.keymap),
+            fps: input(values.fps.map(|fps| fps.to_string())),
+            movement_timeout: input(values.movement_timeout_ms.map(|ms| ms.to_string())),
+            callback_port: values.callback_port,
+            graphics: settings.graphics,
+            start_notification: settings.start_notification,
+            remember_session: settings.remember_session,
+            path,
+            needs_update: true,
+            error_message: None,
+            info_message: None,
+        }
+    }
+
+    fn field(&self) -> Field {
+        FIELDS[self.selection].0
+    }
+
+    fn input(&mut self, field: Field) -> Option<&mut Input> {
+        match field {
+            Field::Host => Some(&mut self.host),
+            Field::Keymap => Some(&mut self.keymap),
+            Field::Fps => Some(&mut self.fps),
+            Field::MovementTimeout => Some(&mut self.movement_timeout),
+            _ => None,
+        }
+    }
+
+    /// Shown value of `field`, with the default in brackets for unset text fields.
+    fn value(&self, field: Field) -> (String, bool) {
+        let text = |input: &Input, default: String| match input.value() {
+            "" => (format!("({})", default), false),
+            value => (value.to_string(), true),
+        };
+        match field {
+            Field::Host => text(&self.host, "ask on start".to_string()),
+            Field::Keymap => text(&self.keymap, "arrows".to_string()),
+            Field::Fps => text(&self.fps, DEFAULT_FPS.to_string()),
+            Field::MovementTimeout => text(&self.movement_timeout, format!("{} ms", DEFAULT_MOVEMENT_TIMEOUT_MS)),
+            Field::Theme => (format!("< {} >", self.theme.as_deref().unwrap_or("default")), true),
+            Field::Graphics => (format!("< {} >", self.graphics.name()), true),
+            Field::StartNotification => (format!("< {} >", self.start_notification.name()), true),
+            Field::RememberSession => (format!("< {} >", if self.remember_session { "yes" } else { "no" }), true),
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(60),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let [fields, hint, message] = Layout::vertical([
+            Constraint::Length(FIELDS.len() as u16 + 2),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .flex(Flex::Center)
+        .areas(horizontal);
+
+        let lines: Vec<Line> = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(index, (field, label))| {
+                let (value, set) = self.value(*field);
+                let value_style = match (index == self.selection, set) {
+                    (true, _) => theme.selection(true),
+                    (false, true) => Style::from(theme.text),
+                    (false, false) => Style::from(theme.muted),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<width$}", label, width = LABEL_WIDTH), theme.text),
+                    Span::styled(value, value_style),
+                ])
+            })
+            .collect();
+        let widget = Paragraph::new(lines).block(
+            theme
+                .block()
+                .title("Settings")
+                .title_bottom("Enter: save  Esc: back"),
+        );
+        frame.render_widget(widget, fields);
+
+        let hint_text = match self.field() {
+            Field::Host => "Host name or base URL preselected on start",
+            Field::Theme | Field::Graphics | Field::StartNotification | Field::RememberSession => "Left/Right: change",
+            Field::Keymap => "Preset and bindings, e.g. wasd,pause=space",
+            Field::Fps => "Frames drawn per second, 10 to 240",
+            Field::MovementTimeout => "Milliseconds without a key until the paddle stops",
+        };
+        let hint_widget = Paragraph::new(hint_text)
+            .style(Style::from(theme.muted))
+            .block(theme.block().title(self.path.clone()));
+        frame.render_widget(hint_widget, hint);
+
+        let field = self.field();
+        let cursor = match field {
+            Field::Host => Some(&self.host),
+            Field::Keymap => Some(&self.keymap),
+            Field::Fps => Some(&self.fps),
+            Field::MovementTimeout => Some(&self.movement_timeout),
+            _ => None,
+        };
+        if let Some(input) = cursor {
+            let width = fields.width.saturating_sub(LABEL_WIDTH as u16 + 3) as usize;
+            let x = input.visual_cursor().min(width) + LABEL_WIDTH + 1;
+            frame.set_cursor_position((fields.x + x as u16, fields.y + 1 + self.selection as u16));
+        }
+
+        if let Some(msg) = &self.error_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .wrap(Wrap { trim: true })
+                .style(Style::from(theme.error))
+                .block(theme.block().title("Error"));
+            frame.render_widget(widget, message);
+        } else if let Some(msg) = &self.info_message {
+            let widget = Paragraph::new(msg.to_owned())
+                .wrap(Wrap { trim: true })
+                .style(Style::from(theme.text))
+                .block(theme.block().title("Info"));
+            frame.render_widget(widget, message);
+        }
+
+        self.needs_update = false;
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        let field = self.field();
+        match key.code {
+            KeyCode::Esc => return Some(PageResults::BackToMenu),
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Up => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down => self.selection = (self.selection + 1).min(FIELDS.len() - 1),
+            KeyCode::Tab => self.selection = (self.selection + 1) % FIELDS.len(),
+            KeyCode::Left => self.cycle(field, false),
+            KeyCode::Right => self.cycle(field, true),
+            _ => (),
+        }
+        if let Some(input) = self.input(field)
+            && !matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::Tab)
+            && (!matches!(key.code, KeyCode::Char(_)) || input.value().len() < VALUE_MAX_LENGTH)
+        {
+            input.handle_event(event);
+        }
+        self.needs_update = true;
+        None
+    }
+
+    /// Steps the choice of `field` forward or back, text fields ignore it.
+    fn cycle(&mut self, field: Field, forward: bool) {
+        fn step<T: Clone + PartialEq>(all: &[T], current: &T, forward: bool) -> T {
+            let index = all.iter().position(|value| value == current).unwrap_or_default();
+            let index = match forward {
+                true => (index + 1) % all.len(),
+                false => (index + all.len() - 1) % all.len(),
+            };
+            all[index].clone()
+        }
+
+        match field {
+            Field::Theme if !self.themes.is_empty() => {
+                let current = self.theme.clone().unwrap_or("default".to_string());
+                self.theme = Some(step(&self.themes, &current, forward));
+            }
+            Field::Graphics => self.graphics = step(&GraphicsMode::ALL, &self.graphics, forward),
+            Field::StartNotification => {
+                self.start_notification = step(&StartNotification::ALL, &self.start_notification, forward)
+            }
+            Field::RememberSession => self.remember_session = !self.remember_session,
+            _ => (),
+        }
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        let text = |input: &Input| Some(input.value().trim().to_string()).filter(|value| !value.is_empty());
+
+        let fps = match text(&self.fps).map(|fps| fps.parse()).transpose() {
+            Ok(fps) => fps,
+            Err(_) => {
+                self.error("Frames per second must be a number".to_string());
+                return None;
+            }
+        };
+        let movement_timeout_ms = match text(&self.movement_timeout).map(|ms| ms.parse()).transpose() {
+            Ok(ms) => ms,
+            Err(_) => {
+                self.error("Movement timeout must be a number of milliseconds".to_string());
+                return None;
+            }
+        };
+
+        Some(PageResults::SaveSettings(Settings {
+            values: Profile {
+                host: text(&self.host),
+                theme: self.theme.clone(),
+                keymap: text(&self.keymap),
+                fps,
+                movement_timeout_ms,
+                callback_port: self.callback_port,
+            },
+            graphics: self.graphics,
+            start_notification: self.start_notification,
+            remember_session: self.remember_session,
+        }))
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.needs_update = true;
+        self.info_message = None;
+        self.error_message = Some(message);
+    }
+
+    pub fn info(&mut self, message: String) {
+        self.needs_update = true;
+        self.error_message = None;
+        self.info_message = Some(message);
+    }
+
+    pub fn needs_update(&self) -> bool {
+        self.needs_update
+    }
+}
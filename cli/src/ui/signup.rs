@@ -0,0 +1,216 @@
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::Paragraph,
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::{auth::LoginErrors, validation};
+
+use super::{pages::PageResults, theme::Theme};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Field {
+    Nickname,
+    Email,
+    Password,
+    Confirm,
+}
+
+const FIELDS: [Field; 4] = [Field::Nickname, Field::Email, Field::Password, Field::Confirm];
+
+impl Field {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Nickname => "Nickname",
+            Self::Email => "E-Mail",
+            Self::Password => "Password",
+            Self::Confirm => "Confirm Password",
+        }
+    }
+
+    fn max_length(&self) -> usize {
+        match self {
+            Self::Nickname => validation::NICKNAME_LENGTH.1,
+            Self::Email => validation::EMAIL_MAX_LENGTH,
+            Self::Password | Self::Confirm => validation::PASSWORD_LENGTH.1,
+        }
+    }
+
+    fn secret(&self) -> bool {
+        matches!(self, Self::Password | Self::Confirm)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SignupPage {
+    inputs: [Input; 4],
+    field_errors: [Option<String>; 4],
+    selected_field: Field,
+    needs_update: bool,
+    error_message: Option<String>,
+}
+
+impl SignupPage {
+    pub fn new() -> Self {
+        Self {
+            inputs: Default::default(),
+            field_errors: Default::default(),
+            selected_field: Field::Nickname,
+            needs_update: true,
+            error_message: None,
+        }
+    }
+
+    fn value(&self, field: Field) -> &str {
+        self.inputs[field as usize].value()
+    }
+
+    fn render_input_field(&self, frame: &mut Frame, theme: &Theme, rect: Rect, field: Field) {
+        let input = &self.inputs[field as usize];
+        let focused = self.selected_field == field;
+        let width = rect.width.saturating_sub(2) as usize;
+        let scroll = input.visual_scroll(width.saturating_sub(1));
+
+        let text = match field.secret() {
+            true => "*".repeat(input.value().len()),
+            false => input.value().to_owned(),
+        };
+
+        let mut block = theme.block().title(field.title());
+        if let Some(error) = &self.field_errors[field as usize] {
+            block = block.title_bottom(Line::styled(error.to_owned(), theme.error));
+        }
+
+        let paragraph = Paragraph::new(text)
+            .style(Style::from(theme.input))
+            .scroll((0, scroll as u16))
+            .block(block);
+        frame.render_widget(paragraph, rect);
+
+        if focused {
+            let x = input.visual_cursor().saturating_sub(scroll) + 1;
+            frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let [_, horizontal, _] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(42),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        let [_, nickname, email, password, confirm, error] = Layout::vertical([
+            Constraint::Percentage(25),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .areas(horizontal);
+
+        for (field, rect) in FIELDS.into_iter().zip([nickname, email, password, confirm]) {
+            self.render_input_field(frame, theme, rect, field);
+        }
+
+        if let Some(msg) = &self.error_message {
+            let style: Style = theme.error.into();
+            let input = Paragraph::new(msg.to_owned())
+                .style(style)
+                .block(theme.block().title("Error"));
+            frame.render_widget(input, error);
+        }
+        self.needs_update = false;
+    }
+
+    pub fn key_event(&mut self, event: &Event) -> Option<PageResults> {
+        let field = self.selected_field;
+        let input = &mut self.inputs[field as usize];
+
+        if let Event::Key(key) = event {
+            match key.code {
+                KeyCode::Esc => return Some(PageResults::Exit),
+                KeyCode::Char(c) if input.value().len() < field.max_length() && c.is_ascii_graphic() => {
+                    input.handle_event(event);
+                    self.needs_update = true;
+                }
+                KeyCode::Backspace | KeyCode::Left | KeyCode::Right => {
+                    input.handle_event(event);
+                    self.needs_update = true;
+                }
+                KeyCode::Tab | KeyCode::Down => self.focus_widget(1),
+                KeyCode::BackTab | KeyCode::Up => self.focus_widget(FIELDS.len() - 1),
+                KeyCode::Enter if field == Field::Confirm => return self.submit(),
+                KeyCode::Enter => self.focus_widget(1),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn validate(&self, field: Field) -> Result<(), String> {
+        let value = self.value(field);
+        match field {
+            Field::Nickname => validation::nickname(value),
+            Field::Email => validation::email(value),
+            Field::Password => validation::password(value),
+            Field::Confirm if value != self.value(Field::Password) => {
+                Err("Passwords don't match".to_string())
+            }
+            Field::Confirm => Ok(()),
+        }
+    }
+
+    fn submit(&mut self) -> Option<PageResults> {
+        self.needs_update = true;
+        self.error_message = None;
+
+        for field in FIELDS {
+            self.field_errors[field as usize] = self.validate(field).err();
+        }
+
+        if let Some(field) = FIELDS.into_iter().find(|field| self.field_errors[*field as usize].is_some()) {
+            self.selected_field = field;
+            return None;
+        }
+
+        Some(PageResults::Signup((
+            self.value(Field::Nickname).to_owned(),
+            self.value(Field::Email).to_owned(),
+            self.value(Field::Password).to_owned(),
+        )))
+    }
+
+    pub fn signup_error(&mut self, error: &LoginErrors) {
+        self.needs_update = true;
+        match error {
+            LoginErrors::EmailTaken => {
+                self.field_errors[Field::Email as usize] = Some(error.to_string());
+                self.selected_field = Field::Email;
+            }
+            _ => self.error_message = Some(error.to_string()),
+        }
+    }
+
+    /// Moves the focus by `offset` fields, checking the field that is left
+    /// once it holds something.
+    fn focus_widget(&mut self, offset: usize) {
+        let field = self.selected_field;
+        if !self.value(field).is_empty() {
+            self.field_errors[field as usize] = self.validate(field).err();
+        }
+
+        self.needs_update = true;
+        self.selected_field = FIELDS[(field as usize + offset) % FIELDS.len()];
+    }
+
+    pub fn needs_update(&self) -> bool {
+        self.needs_update
+    }
+}
@@ -0,0 +1,194 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    symbols::border,
+    widgets::Block,
+};
+use serde::Deserialize;
+
+use crate::capabilities::{Capabilities, Glyphs};
+
+pub const PRESETS: [&str; 4] = ["default", "deuteranopia", "high-contrast", "monochrome"];
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub paddle_a: Color,
+    pub paddle_b: Color,
+    pub ball: Color,
+    pub border: Color,
+    pub text: Color,
+    pub muted: Color,
+    pub input: Color,
+    pub highlight: Color,
+    pub accent: Color,
+    pub score: Color,
+    pub status: Color,
+    pub error: Color,
+    pub ascii_borders: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            paddle_a: Color::Rgb(255, 0, 255),
+            paddle_b: Color::Rgb(0, 255, 255),
+            ball: Color::Rgb(255, 255, 0),
+            border: Color::Reset,
+            text: Color::Reset,
+            muted: Color::Gray,
+            input: Color::Yellow,
+            highlight: Color::Rgb(255, 0, 255),
+            accent: Color::Rgb(34, 211, 238),
+            score: Color::Rgb(253, 224, 71),
+            status: Color::Yellow,
+            error: Color::Red,
+            ascii_borders: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            // Okabe-Ito palette, distinguishable with red-green color blindness
+            "deuteranopia" => Some(Self {
+                paddle_a: Color::Rgb(230, 159, 0),
+                paddle_b: Color::Rgb(86, 180, 233),
+                ball: Color::Rgb(240, 228, 66),
+                border: Color::Reset,
+                text: Color::Reset,
+                muted: Color::Gray,
+                input: Color::Rgb(240, 228, 66),
+                highlight: Color::Rgb(230, 159, 0),
+                accent: Color::Rgb(86, 180, 233),
+                score: Color::Rgb(240, 228, 66),
+                status: Color::Rgb(240, 228, 66),
+                error: Color::Rgb(213, 94, 0),
+                ascii_borders: false,
+            }),
+            "high-contrast" => Some(Self {
+                paddle_a: Color::Rgb(255, 255, 0),
+                paddle_b: Color::Rgb(0, 255, 255),
+                ball: Color::White,
+                border: Color::White,
+                text: Color::White,
+                muted: Color::Gray,
+                input: Color::White,
+                highlight: Color::Rgb(255, 255, 0),
+                accent: Color::Rgb(0, 255, 255),
+                score: Color::White,
+                status: Color::Rgb(255, 255, 0),
+                error: Color::Rgb(255, 64, 64),
+                ascii_borders: false,
+            }),
+            "monochrome" => Some(Self {
+                paddle_a: Color::White,
+                paddle_b: Color::White,
+                ball: Color::White,
+                border: Color::Gray,
+                text: Color::White,
+                muted: Color::DarkGray,
+                input: Color::White,
+                highlight: Color::White,
+                accent: Color::White,
+                score: Color::White,
+                status: Color::White,
+                error: Color::White,
+                ascii_borders: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Downgrades the palette and borders to what the terminal can display.
+    pub fn adapt(self, capabilities: &Capabilities) -> Self {
+        let depth = capabilities.color_depth;
+        Self {
+            paddle_a: depth.downgrade(self.paddle_a),
+            paddle_b: depth.downgrade(self.paddle_b),
+            ball: depth.downgrade(self.ball),
+            border: depth.downgrade(self.border),
+            text: depth.downgrade(self.text),
+            muted: depth.downgrade(self.muted),
+            input: depth.downgrade(self.input),
+            highlight: depth.downgrade(self.highlight),
+            accent: depth.downgrade(self.accent),
+            score: depth.downgrade(self.score),
+            status: depth.downgrade(self.status),
+            error: depth.downgrade(self.error),
+            ascii_borders: capabilities.glyphs == Glyphs::Ascii,
+        }
+    }
+
+    pub fn block(&self) -> Block<'static> {
+        let block = match self.ascii_borders {
+            true => Block::bordered().border_set(ASCII_BORDER),
+            false => Block::bordered(),
+        };
+
+        match self.border {
+            Color::Reset => block,
+            color => block.border_style(color),
+        }
+    }
+
+    pub fn selection(&self, selected: bool) -> Style {
+        match selected {
+            // Without distinct colors, e.g. with NO_COLOR, the selection is reversed
+            true if self.highlight == self.muted => Style::default().add_modifier(Modifier::REVERSED),
+            true => self.highlight.into(),
+            false => self.muted.into(),
+        }
+    }
+}
+
+/// User defined theme from the config file. Every color that is left out is
+/// taken from the `base` preset.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub paddle_a: Option<Color>,
+    pub paddle_b: Option<Color>,
+    pub ball: Option<Color>,
+    pub border: Option<Color>,
+    pub text: Option<Color>,
+    pub muted: Option<Color>,
+    pub input: Option<Color>,
+    pub highlight: Option<Color>,
+    pub accent: Option<Color>,
+    pub score: Option<Color>,
+    pub status: Option<Color>,
+    pub error: Option<Color>,
+}
+
+impl ThemeConfig {
+    pub fn apply(&self, base: Theme) -> Theme {
+        Theme {
+            paddle_a: self.paddle_a.unwrap_or(base.paddle_a),
+            paddle_b: self.paddle_b.unwrap_or(base.paddle_b),
+            ball: self.ball.unwrap_or(base.ball),
+            border: self.border.unwrap_or(base.border),
+            text: self.text.unwrap_or(base.text),
+            muted: self.muted.unwrap_or(base.muted),
+            input: self.input.unwrap_or(base.input),
+            highlight: self.highlight.unwrap_or(base.highlight),
+            accent: self.accent.unwrap_or(base.accent),
+            score: self.score.unwrap_or(base.score),
+            status: self.status.unwrap_or(base.status),
+            error: self.error.unwrap_or(base.error),
+            ascii_borders: base.ascii_borders,
+        }
+    }
+}
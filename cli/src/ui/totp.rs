@@ -2,14 +2,14 @@ use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    style::Style,
+    widgets::Paragraph,
 };
 use tui_input::{Input, backend::crossterm::EventHandler};
 
 use crate::auth::TotpErrors;
 
-use super::pages::PageResults;
+use super::{pages::PageResults, theme::Theme};
 
 #[derive(Debug, Clone)]
 pub struct TotpPage {
@@ -30,18 +30,19 @@ impl TotpPage {
     fn render_input_field(
         &self,
         frame: &mut Frame,
+        theme: &Theme,
         rect: Rect,
         field: &Input,
         title: &str,
 
     ) {
-        let style: Style = Color::Yellow.into();
+        let style: Style = theme.input.into();
 
         let text = field.value().to_owned();
 
         let input = Paragraph::new(text)
             .style(style)
-            .block(Block::bordered().title(title));
+            .block(theme.block().title(title));
 
         frame.render_widget(input, rect);
 
@@ -49,7 +50,7 @@ impl TotpPage {
         frame.set_cursor_position((rect.x + x as u16, rect.y + 1));
     }
 
-    pub fn render(&mut self, frame: &mut Frame) {
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
         let [_, horizontal, _] = Layout::horizontal([
             Constraint::Min(0),
             Constraint::Length(42),
@@ -66,16 +67,17 @@ impl TotpPage {
 
         self.render_input_field(
             frame,
+            theme,
             totp,
             &self.totp_code,
             "2FA Code",
         );
 
         if let Some(msg) = &self.error_message {
-            let style: Style = Color::Red.into();
+            let style: Style = theme.error.into();
             let input = Paragraph::new(msg.to_owned())
                 .style(style)
-                .block(Block::bordered().title("Error"));
+                .block(theme.block().title("Error"));
             frame.render_widget(input, error);
         }
         self.needs_update = false;
@@ -85,11 +87,9 @@ impl TotpPage {
         if let Event::Key(key) = event {
             match key.code {
                 KeyCode::Esc => return Some(PageResults::Exit),
-                KeyCode::Char(c) => {
-                    if self.totp_code.value().len() < 6 && c.is_ascii_digit() {
-                        self.totp_code.handle_event(event);
-                        self.needs_update = true;
-                    }
+                KeyCode::Char(c) if self.totp_code.value().len() < 6 && c.is_ascii_digit() => {
+                    self.totp_code.handle_event(event);
+                    self.needs_update = true;
                 }
                 KeyCode::Backspace => {
                     self.totp_code.handle_event(event);
@@ -24,10 +24,10 @@ impl Shape for Ball {
             for x in x0..=x1 {
                 let dx = (x as f64) - self.x;
 
-                if dx * dx + dy * dy <= self.radius * self.radius {
-                    if let Some((px, py)) = painter.get_point(x as f64, y as f64) {
-                        painter.paint(px, py, self.color);
-                    }
+                if dx * dx + dy * dy <= self.radius * self.radius
+                    && let Some((px, py)) = painter.get_point(x as f64, y as f64)
+                {
+                    painter.paint(px, py, self.color);
                 }
             }
         }
@@ -1,2 +1,3 @@
 pub mod ball;
 pub mod filledrectangle;
+pub mod qr;
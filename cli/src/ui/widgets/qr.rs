@@ -0,0 +1,45 @@
+use qrcode::{Color as Module, QrCode};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+/// Modules of white space around the code, scanners need it to find the code.
+const QUIET_ZONE: usize = 2;
+
+/// Draws `data` as a QR code with two modules per cell using upper half
+/// blocks. The colors are fixed to black on white since most scanners fail on
+/// inverted codes, whatever the theme is.
+pub fn half_blocks(data: &str) -> Option<Vec<Line<'static>>> {
+    let code = QrCode::new(data.as_bytes()).ok()?;
+    let width = code.width();
+    let modules = code.to_colors();
+    let size = width + 2 * QUIET_ZONE;
+
+    let dark = |x: usize, y: usize| {
+        let (x, y) = (x.checked_sub(QUIET_ZONE)?, y.checked_sub(QUIET_ZONE)?);
+        (x < width && y < width).then(|| modules[y * width + x] == Module::Dark)
+    };
+    let color = |x, y| match dark(x, y) {
+        Some(true) => Color::Black,
+        _ => Color::White,
+    };
+
+    let lines = (0..size)
+        .step_by(2)
+        .map(|y| {
+            let spans: Vec<Span> = (0..size)
+                .map(|x| Span::styled("▀", Style::new().fg(color(x, y)).bg(color(x, y + 1))))
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+
+    Some(lines)
+}
+
+/// Size of the code from `half_blocks` in cells.
+pub fn size(lines: &[Line]) -> (u16, u16) {
+    let width = lines.first().map(|line| line.width()).unwrap_or_default();
+    (width as u16, lines.len() as u16)
+}
@@ -0,0 +1,54 @@
+//! Client-side copies of the account rules of the auth-user-service, so
+//! mistakes show up before a request is sent.
+
+pub const NICKNAME_LENGTH: (usize, usize) = (3, 20);
+pub const EMAIL_MAX_LENGTH: usize = 254;
+pub const PASSWORD_LENGTH: (usize, usize) = (8, 128);
+
+pub fn nickname(nickname: &str) -> Result<(), String> {
+    let (min, max) = NICKNAME_LENGTH;
+    if nickname.len() < min || nickname.len() > max {
+        return Err(format!("Must be {} to {} characters", min, max));
+    }
+    if !nickname
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err("Only letters, digits, _ - and .".to_string());
+    }
+    Ok(())
+}
+
+pub fn email(email: &str) -> Result<(), String> {
+    if email.len() > EMAIL_MAX_LENGTH {
+        return Err(format!("At most {} characters", EMAIL_MAX_LENGTH));
+    }
+
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain
+                .split_once('.')
+                .is_some_and(|(name, tld)| !name.is_empty() && !tld.is_empty())
+            && !domain.ends_with('.')
+            && !email.contains(char::is_whitespace)
+    });
+
+    match valid {
+        true => Ok(()),
+        false => Err("Not a valid e-mail address".to_string()),
+    }
+}
+
+pub fn password(password: &str) -> Result<(), String> {
+    let (min, max) = PASSWORD_LENGTH;
+    if password.len() < min || password.len() > max {
+        return Err(format!("Must be {} to {} characters", min, max));
+    }
+
+    let has = |check: fn(&char) -> bool| password.chars().any(|c| check(&c));
+    if !has(char::is_ascii_digit) || !has(char::is_ascii_lowercase) || !has(char::is_ascii_uppercase) {
+        return Err("Needs a digit, a lower- and an uppercase letter".to_string());
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use std::{error::Error, fmt::Display};
 
+use super::events::errors::EventError;
+
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum WebSocketErrors {
     ConnectionError,
@@ -26,3 +29,12 @@ impl Display for WebSocketErrors {
 }
 
 impl Error for WebSocketErrors {}
+
+impl From<WebSocketErrors> for EventError {
+    fn from(err: WebSocketErrors) -> Self {
+        match err {
+            WebSocketErrors::InvalidCredentials => Self::InvalidCredentials,
+            _ => Self::ConnectionError,
+        }
+    }
+}
@@ -10,6 +10,7 @@ pub enum EventError {
     InvalidResponse,
     CreateRoomError,
     JoinRoomError,
+    InvalidCredentials,
 }
 
 impl Display for EventError {
@@ -20,6 +21,7 @@ impl Display for EventError {
             Self::InvalidResponse => write!(f, "Invalid Response"),
             Self::CreateRoomError => write!(f, "Unable to create room"),
             Self::JoinRoomError => write!(f, "Unable to join room"),
+            Self::InvalidCredentials => write!(f, "Session expired, please log in again"),
         }
     }
 }
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -50,6 +52,15 @@ pub enum NumberString {
   String(String)
 }
 
+impl NumberString {
+    pub fn as_id(&self) -> Option<u32> {
+        match self {
+            Self::Number(id) => Some(*id),
+            Self::String(id) => id.parse().ok(),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct GameStartEventPlayer {
     pub id: NumberString,
@@ -0,0 +1,155 @@
+use futures_util::SinkExt;
+use serde_json::{Value, json};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{SocketIoClient, errors::WebSocketErrors, events::errors::EventError};
+
+#[derive(Debug)]
+pub enum LiveChatEvents {
+    JoinTheRoom(String),
+    CanYouPlay(u32),
+    InvitationDeclined(u32),
+    Other,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InvitationStatus {
+    Sent,
+    AlreadySent,
+    AlreadyReceived,
+    Offline,
+    Refused,
+}
+
+enum Packet {
+    Event(Vec<Value>),
+    Ack(usize, Vec<Value>),
+}
+
+/// Client for the live chat namespace, which carries game invitations between friends.
+#[derive(Debug)]
+pub struct LiveChatClient {
+    client: SocketIoClient,
+    next_ack_id: usize,
+}
+
+impl LiveChatClient {
+    pub async fn new(url: &str, token: &str) -> Result<Self, WebSocketErrors> {
+        Ok(Self {
+            client: SocketIoClient::new(url, token).await?,
+            next_ack_id: 0,
+        })
+    }
+
+    async fn emit(&mut self, args: Value, ack_id: Option<usize>) -> Result<(), EventError> {
+        let ack_id = ack_id.map(|id| id.to_string()).unwrap_or_default();
+        let data = format!("42{}{}", ack_id, args);
+
+        self.client
+            .socket
+            .send(Message::Text(data.into()))
+            .await
+            .map_err(|_| EventError::ConnectionError)
+    }
+
+    /// Emits an event and waits for the server to acknowledge it. Events that
+    /// arrive in the meantime are dropped.
+    async fn call(&mut self, args: Value) -> Result<Vec<Value>, EventError> {
+        let ack_id = self.next_ack_id;
+        self.next_ack_id += 1;
+        self.emit(args, Some(ack_id)).await?;
+
+        loop {
+            if let Packet::Ack(id, values) = self.next_packet().await?
+                && id == ack_id
+            {
+                return Ok(values);
+            }
+        }
+    }
+
+    async fn next_packet(&mut self) -> Result<Packet, EventError> {
+        loop {
+            let Message::Text(text) = self.client.next_message().await? else {
+                continue;
+            };
+
+            let Some(pos) = text.find('[') else {
+                continue;
+            };
+            let values: Vec<Value> =
+                serde_json::from_str(&text[pos..]).map_err(EventError::SerializingError)?;
+
+            match &text[..pos] {
+                "42" => return Ok(Packet::Event(values)),
+                prefix if prefix.starts_with("43") => {
+                    let id = prefix[2..].parse().map_err(|_| EventError::InvalidResponse)?;
+                    return Ok(Packet::Ack(id, values));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Records a game invitation to `target_id`, the server tells the target about it.
+    pub async fn invite(&mut self, target_id: u32) -> Result<InvitationStatus, EventError> {
+        let ack = self
+            .call(json!(["record or check game invitation", target_id, "record"]))
+            .await?;
+
+        match ack.first().and_then(Value::as_str) {
+            Some("success") => Ok(InvitationStatus::Sent),
+            Some("sent") => Ok(InvitationStatus::AlreadySent),
+            Some("received") => Ok(InvitationStatus::AlreadyReceived),
+            Some("offline") => Ok(InvitationStatus::Offline),
+            Some(_) => Ok(InvitationStatus::Refused),
+            None => Err(EventError::InvalidResponse),
+        }
+    }
+
+    /// `option` is "sent" to withdraw an own invitation and "received" to decline one.
+    pub async fn delete_invitation(&mut self, target_id: u32, option: &str) -> Result<(), EventError> {
+        self.call(json!(["delete invitation", target_id, option]))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn room_id_created(&mut self, room_id: &str, target_id: u32) -> Result<(), EventError> {
+        self.emit(json!(["room id created", room_id, target_id]), None)
+            .await
+    }
+
+    pub async fn status_response(&mut self, status: &str, to_id: u32) -> Result<(), EventError> {
+        self.emit(json!(["status response", status, to_id]), None)
+            .await
+    }
+
+    pub async fn wait_for_events(&mut self) -> Result<LiveChatEvents, EventError> {
+        let values = loop {
+            if let Packet::Event(values) = self.next_packet().await? {
+                break values;
+            }
+        };
+
+        let id = |value: Option<&Value>| value.and_then(Value::as_u64).map(|id| id as u32);
+
+        match values.first().and_then(Value::as_str) {
+            Some("join the room") => values
+                .get(1)
+                .and_then(Value::as_str)
+                .map(|room_id| LiveChatEvents::JoinTheRoom(room_id.to_string()))
+                .ok_or(EventError::InvalidResponse),
+            Some("can you play") => id(values.get(1))
+                .map(LiveChatEvents::CanYouPlay)
+                .ok_or(EventError::InvalidResponse),
+            Some("invitation declined") => id(values.get(1))
+                .map(LiveChatEvents::InvitationDeclined)
+                .ok_or(EventError::InvalidResponse),
+            _ => Ok(LiveChatEvents::Other),
+        }
+    }
+
+    pub async fn close(&mut self) -> Result<(), EventError> {
+        self.client.close().await
+    }
+}
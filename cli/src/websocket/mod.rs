@@ -1,6 +1,7 @@
 mod errors;
 pub mod events;
 mod handshake;
+pub mod livechat;
 
 use std::error::Error;
 
@@ -128,14 +129,7 @@ impl SocketIoClient {
             .await
             .map_err(|_| EventError::ConnectionError)?;
 
-        let msg = self
-            .socket
-            .next()
-            .await
-            .ok_or(EventError::ConnectionError)?
-            .map_err(|_| EventError::ConnectionError);
-
-        match msg? {
+        match self.next_message().await? {
             Message::Text(text) => {
                 let (code, json) =
                     Self::split_code_json(&text).ok_or(EventError::ConnectionError)?;
@@ -153,6 +147,27 @@ impl SocketIoClient {
         }
     }
 
+    /// Next message that is not an Engine.IO ping, pings are answered on the way.
+    async fn next_message(&mut self) -> Result<Message, EventError> {
+        loop {
+            let msg = self
+                .socket
+                .next()
+                .await
+                .ok_or(EventError::ConnectionError)?
+                .map_err(|_| EventError::ConnectionError)?;
+
+            match msg {
+                Message::Text(text) if text == "2" => self
+                    .socket
+                    .send("3".into())
+                    .await
+                    .map_err(|_| EventError::ConnectionError)?,
+                msg => return Ok(msg),
+            }
+        }
+    }
+
     async fn send_event_noresponse(&mut self, event: &EventRequest) -> Result<(), EventError> {
         let data = format!(
             "42{}",
@@ -231,6 +246,11 @@ impl SocketIoClient {
         .await
     }
 
+    pub async fn leave_room(&mut self) -> Result<(), EventError> {
+        self.send_event_noresponse(&EventRequest::new("leave_room", &EventTypes::LeaveRoom))
+            .await
+    }
+
     pub async fn close(&mut self) -> Result<(), EventError> {
         self.socket
             .close(None)
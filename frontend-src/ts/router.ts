@@ -587,12 +587,18 @@ async function showOAuthResultPage() {
         // console.log(data);
         if (data.token && data.cli_redirect) {
           const nickname_required = data.action_required === 'nickname';
-          const cli_callback_result = await fetch('http://localhost:' + data.cli_redirect + '?token=' + data.token + '&nickname_required=' + nickname_required);
-          if (cli_callback_result.ok) {
+          const cli_callback_url = 'http://localhost:' + data.cli_redirect
+            + '?token=' + encodeURIComponent(data.token)
+            + '&nickname_required=' + nickname_required
+            + '&state=' + encodeURIComponent(data.cli_state ?? '');
+          const cli_callback_result = await fetch(cli_callback_url).catch(() => null);
+          if (cli_callback_result?.ok) {
             header = 'Authentication successful';
             text = 'You can switch back to the CLI now.';
           } else {
-            text = 'Something when wrong when redirect you back to the CLI.';
+            // The CLI runs on another machine, e.g. over SSH: it takes the URL pasted
+            header = 'Almost done';
+            text = 'The CLI could not be reached from this browser. Paste this into the CLI to finish the login: ' + cli_callback_url;
           }
         }
         else if (data.token && data.action_required !== false) {
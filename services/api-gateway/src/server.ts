@@ -99,6 +99,7 @@ async function buildServer() {
       '/api/login',
       '/api/auth/42',
       '/api/auth/42/callback',
+      '/api/auth/refresh',
       '/api/logout',
       '/api/health',
       '/api/profile/avatars', // ✅ BU SATIRI EKLEYİN
@@ -187,6 +188,10 @@ async function buildServer() {
     reply.code(200).send({ status: 'ok' });
   });
 
+  await server.get('/api/health', async () => {
+    return { status: 'ok' };
+  });
+
   // === ROUTE GAME SERVICE ===
   await server.register(proxy, {
     upstream: upstreamGameService || 'https://localhost:3001',
@@ -5,6 +5,7 @@ import User from './user';
 import { OAUTH_REDIRECT_URL, liveChatUpstream } from './server';
 import z, { ZodError } from 'zod';
 import * as OTPAuth from "otpauth";
+import RefreshTokenService from './refreshToken';
 
 interface SignupBody {
   nickname: string;
@@ -55,6 +56,10 @@ const Login2FaRequestSchema = z.object({
   totp_code: z.string().length(6),
 });
 
+const RefreshRequestSchema = z.object({
+  refresh_token: z.string().min(1),
+});
+
 const UpdateAccountRequestSchema = z.object({
   email: Email,
   current_password: Password,
@@ -63,11 +68,13 @@ const UpdateAccountRequestSchema = z.object({
 
 const DeleteAccountRequestSchema = z.object({
   password: Password.nullable(),
+  totp_code: z.string().length(6).nullish(),
 });
 
 export default class AuthController {
   private fastify: any;
   private totp_secrets_tmp: Map<number, string>;
+  private refreshTokens: RefreshTokenService;
   constructor(
     private authService: AuthService,
     private oAuthService: OAuthService,
@@ -75,6 +82,7 @@ export default class AuthController {
   ) {
     this.fastify = fastifyInstance;
     this.totp_secrets_tmp = new Map();
+    this.refreshTokens = new RefreshTokenService(fastifyInstance);
   }
 
   // ======= EXISTING AUTH METHODS =======
@@ -112,6 +120,7 @@ export default class AuthController {
       return reply.status(201).send({
         success: true,
         token,
+        refresh_token: this.refreshTokens.create(user.id!),
         action_required: false,
         user: {
           id: user.id,
@@ -175,6 +184,7 @@ export default class AuthController {
       return reply.send({
         success: true,
         token,
+        refresh_token: this.refreshTokens.create(user.id!),
         action_required,
         user: {
           id: user.id,
@@ -233,6 +243,7 @@ export default class AuthController {
       return reply.send({
         success: true,
         token: signToken,
+        refresh_token: this.refreshTokens.create(user.id!),
         action_required: false,
         user: {
           id: user.id,
@@ -404,6 +415,10 @@ export default class AuthController {
       }
 
       if (this.authService.updateAccount(Number(sub), result.email, hashedPassword)) {
+        if (result.new_password) {
+          // Logins with the old password don't stay renewable
+          this.refreshTokens.revokeAll(user.id);
+        }
         return reply.send({success: true});
       }
       return reply.status(409).send({error: 'E-Mail already in use'});
@@ -445,7 +460,13 @@ export default class AuthController {
           return reply.status(401).send({ error: 'Invalid password' });
         }
         if (user.totp_secret !== null) {
-          return reply.status(401).send({error: 'You need to disable 2FA first!'});
+          if (!result.totp_code) {
+            return reply.status(401).send({error: 'You need to disable 2FA first!'});
+          }
+          const totp = new OTPAuth.TOTP({secret: user.totp_secret});
+          if (totp.validate({token: result.totp_code, window: 1}) === null) {
+            return reply.status(401).send({error: 'Invalid 2FA Code'});
+          }
         }
       }
       this.authService.deleteAccount(Number(sub));
@@ -476,10 +497,10 @@ export default class AuthController {
     }
   }
 
-  async oAuthLogin(request: FastifyRequest<{Querystring: {cli_port?: number}}>, reply: FastifyReply) {
-    const { cli_port } = request.query;
+  async oAuthLogin(request: FastifyRequest<{Querystring: {cli_port?: number, cli_state?: string}}>, reply: FastifyReply) {
+    const { cli_port, cli_state } = request.query;
 
-    const state = this.oAuthService.generateRandomState(cli_port);
+    const state = this.oAuthService.generateRandomState(cli_port, cli_state);
 
     const callbackUrl = OAUTH_REDIRECT_URL + '/oAuthCallback';
 
@@ -502,6 +523,7 @@ export default class AuthController {
       const user = this.authService.getUserByExternalId(profileInfos.id);
 
       const cli_redirect = client.type === OAuthClientTypes.Cli ? client.cli_port : false;
+      const cli_state = client.type === OAuthClientTypes.Cli ? client.cli_state : null;
       if (user) {
         this.authService.updateUserStatus(user.id!, 'online');
 
@@ -513,7 +535,8 @@ export default class AuthController {
             success: true,
             token,
             action_required,
-            cli_redirect
+            cli_redirect,
+            cli_state,
           });
         }
 
@@ -522,6 +545,7 @@ export default class AuthController {
           token,
           action_required,
           cli_redirect,
+          cli_state,
           user: {
             id: user.id,
             nickname: user.nickname,
@@ -550,6 +574,7 @@ export default class AuthController {
         token,
         action_required: 'nickname',
         cli_redirect,
+        cli_state,
         user: {
           id: newUser.id,
           nickname: newUser.nickname,
@@ -571,6 +596,45 @@ export default class AuthController {
     }
   }
 
+  async refresh(request: FastifyRequest, reply: FastifyReply) {
+    try {
+      const { refresh_token } = await RefreshRequestSchema.parseAsync(request.body);
+
+      const userId = this.refreshTokens.consume(refresh_token);
+      if (userId === null) {
+        return reply.status(401).send({ error: 'Invalid refresh token' });
+      }
+
+      const user = this.authService.getUserById(userId);
+      if (!user || !user.id) {
+        return reply.status(401).send({ error: 'Invalid refresh token' });
+      }
+
+      // Refresh tokens are only handed out once 2FA is done
+      const token = await this.signUserInfos(user, true);
+
+      return reply.send({
+        success: true,
+        token,
+        refresh_token: this.refreshTokens.create(user.id),
+      });
+    } catch (error) {
+      this.fastify.log.error(error);
+      if (error instanceof ZodError) {
+        return reply.status(400).send({
+          error: 'Bad request',
+        });
+      }
+      return reply.status(401).send({
+        error: 'Refresh failed'
+      });
+    }
+  }
+
+  createRefreshToken(user: User) {
+    return this.refreshTokens.create(user.id!);
+  }
+
   async signUserInfos(user: User, totp_success?: boolean) {
     const token = await this.fastify.vAuth.sign({
       sub: user.id?.toString(),
@@ -9,6 +9,8 @@ export enum OAuthClientTypes {
 interface OAuthClient {
   type: OAuthClientTypes;
   cli_port: number | null;
+  // Random value of the CLI, handed back with the token so its callback listener can tell the login apart
+  cli_state: string | null;
 }
 
 export default class OAuthService {
@@ -20,7 +22,7 @@ export default class OAuthService {
     this.states = new Map<string, OAuthClient>();
   }
 
-  generateRandomState(cli_port?: number) {
+  generateRandomState(cli_port?: number, cli_state?: string) {
     const characters = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789';
     const length = 32;
 
@@ -33,7 +35,7 @@ export default class OAuthService {
     const type = cli_port !== undefined ? OAuthClientTypes.Cli : OAuthClientTypes.Website;
     const port = cli_port !== undefined ? cli_port : null;
 
-    this.states.set(result, {type, cli_port: port});
+    this.states.set(result, {type, cli_port: port, cli_state: cli_state ?? null});
 
     return result;
   }
@@ -1,15 +1,61 @@
-import db from './db';
+import * as crypto from 'crypto';
+import { parseDurationToSec } from './parseTimeUnits';
 
-export default class refreshTokenController {
+// REFRESH TOKENS
+// Only the sha256 of a refresh token is stored. Each token can be used once:
+// trading it in deletes it and hands out the next one.
+export default class RefreshTokenService {
   private db: any;
+  private expiresInSec: number;
   constructor(fastify: any) {
     if (!fastify.db) {
       throw new Error('Database not initialized');
     }
     this.db = fastify.db;
+    this.expiresInSec = parseDurationToSec(process.env.REFRESH_TOKEN_EXPIRATION || '30d');
     this.initializeDatabase();
   }
+
   private initializeDatabase() {
-    this.db.connect();
+    this.db.exec(`
+      CREATE TABLE IF NOT EXISTS refresh_tokens (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL,
+        token_hash TEXT NOT NULL UNIQUE,
+        expires_at INTEGER NOT NULL,
+        created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+        FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+      )
+    `);
+  }
+
+  private hash(token: string): string {
+    return crypto.createHash('sha256').update(token).digest('hex');
+  }
+
+  create(userId: number): string {
+    const token = crypto.randomBytes(32).toString('base64url');
+    const expiresAt = Math.floor(Date.now() / 1000) + this.expiresInSec;
+
+    this.db
+      .prepare('INSERT INTO refresh_tokens (user_id, token_hash, expires_at) VALUES (?, ?, ?)')
+      .run(userId, this.hash(token), expiresAt);
+    return token;
+  }
+
+  // Returns the user id of a valid token and deletes it, null when the token
+  // is unknown or expired
+  consume(token: string): number | null {
+    const row = this.db
+      .prepare('DELETE FROM refresh_tokens WHERE token_hash = ? RETURNING user_id, expires_at')
+      .get(this.hash(token));
+    if (!row || row.expires_at <= Math.floor(Date.now() / 1000)) {
+      return null;
+    }
+    return row.user_id;
+  }
+
+  revokeAll(userId: number) {
+    this.db.prepare('DELETE FROM refresh_tokens WHERE user_id = ?').run(userId);
   }
 }
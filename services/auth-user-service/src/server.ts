@@ -91,6 +91,10 @@ interface TotpBody {
   totp_code: string;
 }
 
+interface RefreshBody {
+  refresh_token: string;
+}
+
 interface UpdateAccountBody {
   email: string;
   current_password: string;
@@ -99,6 +103,7 @@ interface UpdateAccountBody {
 
 interface DeleteAccountBody {
   password: string | null;
+  totp_code?: string | null;
 }
 
 // I added new features don't delete
@@ -202,7 +207,7 @@ async function start() {
     return result;
   });
 
-  server.get<{Querystring: {cli_port?: number}}>('/api/auth/42', async (request, reply) => {
+  server.get<{Querystring: {cli_port?: number, cli_state?: string}}>('/api/auth/42', async (request, reply) => {
     if (!oAuthService.envVariablesConfigured()) {
       return reply.status(503).send({
         error: 'OAuth service temporarily unavailable',
@@ -244,6 +249,16 @@ async function start() {
     return result;
   });
 
+  server.post<{Body: RefreshBody}>('/api/auth/refresh', async (request, reply) => {
+    request.log.info({headers: request.headers}, 'Incoming refresh headers');
+
+    const result = await authController.refresh(request, reply);
+
+    request.log.info('Refresh response');
+
+    return result;
+  });
+
   server.post<{Body: TotpBody}>('/api/auth/2fa/enable', async (request, reply) => {
     request.log.info({headers: request.headers}, 'Incoming enable 2fa headers');
 
@@ -370,8 +385,12 @@ async function start() {
           const {nickname} = await nicknameSchema.parseAsync(request.body);
           const result = authService.setNickname(user.id, nickname);
           const signToken = await authController.signUserInfos(result);
+          // Accounts with 2FA get theirs after the code
+          const refresh_token = result.totp_secret === null
+            ? authController.createRefreshToken(result)
+            : undefined;
 
-          return reply.send({ success: true, token: signToken, user: result });
+          return reply.send({ success: true, token: signToken, refresh_token, user: result });
         } catch (error) {
           if (error instanceof ZodError) {
             return reply.send({success: false, error: 'Nickname doesn\'t meet the requirements'});